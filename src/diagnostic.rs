@@ -0,0 +1,247 @@
+//! Rendering of error messages with source snippets.
+use std::fmt::Write;
+use std::io::IsTerminal;
+use crate::{LexSource,SourceMap,Span,StrTokenizer};
+use crate::zero_copy::find_line_break;
+
+// at most this many lines of a span are shown
+const MAX_SHOWN_LINES:usize = 4;
+
+/// Severity of a [Diagnostic]
+#[cfg_attr(feature = "serde", derive(serde::Serialize,serde::Deserialize))]
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum Severity
+{
+   /// a problem that prevents the input from being processed
+   Error,
+   /// a likely mistake that does not prevent processing
+   Warning,
+   /// additional information, usually about another diagnostic
+   Note,
+   /// a suggestion of how to fix a problem
+   Help,
+}
+impl Severity
+{
+  /// the label printed before the message, such as "error"
+  pub fn label(&self) -> &'static str
+  {
+    match self {
+      Severity::Error => "error",
+      Severity::Warning => "warning",
+      Severity::Note => "note",
+      Severity::Help => "help",
+    }
+  }
+  // ansi color code of the label
+  fn color(&self) -> &'static str
+  {
+    match self {
+      Severity::Error => "\x1b[1;31m",
+      Severity::Warning => "\x1b[1;33m",
+      Severity::Note => "\x1b[1;36m",
+      Severity::Help => "\x1b[1;32m",
+    }
+  }
+}//impl Severity
+
+/// An error or other message about a range of bytes of a source, which is
+/// rendered along with the path of the source, the lines that contain the
+/// range with carets under the range itself, and optional notes:
+///```text
+/// error: unclosed string
+///  --> input.txt:3:9
+///   |
+/// 3 |   x = y+"abc;
+///   |         ^
+///   = note: strings may span several lines
+///```
+/// Diagnostics can be created for tokenizer errors with
+/// [StrTokenizer::error_diagnostic](crate::StrTokenizer::error_diagnostic),
+/// and for errors found by a parser from the positions of its tokens.
+///
+/// Example:
+///```ignore
+///  let source = LexSource::new("input.txt").unwrap();
+///  let mut tokenizer = StrTokenizer::from_source(&source);
+///  while let Some((token,line,column)) = tokenizer.next() {
+///    if let RawToken::LexError = token {
+///      tokenizer.error_diagnostic().unwrap().emit(&source);
+///    }
+///  }
+///  Diagnostic::warning("unused variable", 120, 125)
+///    .with_note("prefix the name with _ to silence this warning")
+///    .emit(&source);
+///```
+#[cfg_attr(feature = "serde", derive(serde::Serialize,serde::Deserialize))]
+#[derive(Clone,Debug,PartialEq)]
+pub struct Diagnostic
+{
+   /// severity of the diagnostic
+   pub severity: Severity,
+   /// main message of the diagnostic
+   pub message: String,
+   /// byte position of the start of the range in the source
+   pub start: usize,
+   /// byte position after the end of the range in the source
+   pub end: usize,
+   /// notes printed after the snippet of the source
+   pub notes: Vec<String>,
+}
+impl Diagnostic
+{
+  /// creates a diagnostic about the bytes from start (inclusive) to end
+  /// (exclusive) of a source
+  pub fn new<M:Into<String>>(severity:Severity, message:M, start:usize, end:usize) -> Diagnostic
+  {
+    Diagnostic { severity, message:message.into(), start, end, notes:Vec::new() }
+  }
+  /// creates an error diagnostic
+  pub fn error<M:Into<String>>(message:M, start:usize, end:usize) -> Diagnostic
+  { Diagnostic::new(Severity::Error,message,start,end) }
+  /// creates a warning diagnostic
+  pub fn warning<M:Into<String>>(message:M, start:usize, end:usize) -> Diagnostic
+  { Diagnostic::new(Severity::Warning,message,start,end) }
+  /// adds a note, returning the diagnostic
+  pub fn with_note<M:Into<String>>(mut self, note:M) -> Diagnostic
+  {
+    self.notes.push(note.into());
+    self
+  }
+
+  /// renders the diagnostic about the given source as a string, with ansi
+  /// colors if color is true
+  pub fn render(&self, source:&LexSource, color:bool) -> String
+  {
+    self.render_text(source.get_path(),source.get_contents(),color)
+  }
+  /// renders the diagnostic about the input of the given tokenizer, with
+  /// ansi colors if color is true.  Lines are broken and columns are
+  /// counted as by the tokenizer, following its
+  /// [StrTokenizer::unicode_newlines] flag and [StrTokenizer::tab_width].
+  pub fn render_for(&self, tokenizer:&StrTokenizer, color:bool) -> String
+  {
+    self.render_lines(tokenizer.get_source(),tokenizer.get_input(),color,
+                      tokenizer.tab_width(),tokenizer.unicode_newlines)
+  }
+  /// renders the diagnostic about the given text, which comes from the
+  /// given path, as a string, with ansi colors if color is true.  Lines
+  /// and columns are counted as by a [StrTokenizer] with default settings:
+  /// lines end with `\n`, `\r\n` or `\r`, and columns are counted in bytes.
+  pub fn render_text(&self, path:&str, text:&str, color:bool) -> String
+  {
+    self.render_lines(path,text,color,0,false)
+  }
+  // renders the diagnostic, expanding tabs to tab stops if tab_width is
+  // not zero, and otherwise printing them as they are
+  fn render_lines(&self, path:&str, text:&str, color:bool, tab_width:usize, unicode:bool) -> String
+  {
+    let paint = |code:&'static str| if color {code} else {""};
+    let (bold,blue,reset) = (paint("\x1b[1m"),paint("\x1b[1;34m"),paint("\x1b[0m"));
+    let sev = paint(self.severity.color());
+    let start = floor_boundary(text,self.start);
+    let end = floor_boundary(text,self.end).max(start);
+    let (mut firstline,mut linestart) = (1,0);
+    while let Some((i,_,n)) = find_line_break(text,linestart,unicode) {
+      if i+n>start {break;}
+      firstline += 1;
+      linestart = i+n;
+    }
+    // a range that ends with a line terminator does not extend to the next line
+    let (mut lastline,mut ls) = (firstline,linestart);
+    while let Some((i,_,n)) = find_line_break(text,ls,unicode) {
+      if i+n>=end {break;}
+      lastline += 1;
+      ls = i+n;
+    }
+    let shownlast = lastline.min(firstline+MAX_SHOWN_LINES-1);
+    let width = shownlast.to_string().len();
+    let pad = " ".repeat(width);
+    let mut out = String::new();
+    let _ = writeln!(out,"{}{}{}: {}{}{}",sev,self.severity.label(),reset,bold,self.message,reset);
+    let column = if tab_width==0 {start-linestart+1} else {expand_tabs(&text[linestart..start],tab_width).1+1};
+    let _ = writeln!(out,"{}{}-->{} {}:{}:{}",pad,blue,reset,path,firstline,column);
+    let _ = writeln!(out,"{} {}|{}",pad,blue,reset);
+    for line in firstline..=shownlast
+    {
+      let (lineend,next) = match find_line_break(text,linestart,unicode) {
+        Some((i,_,n)) => (i,i+n),
+        None => (text.len(),text.len()),
+      };
+      let linetext = &text[linestart..lineend];
+      let (shown,_) = expand_tabs(linetext,tab_width);
+      let (mut before,mut marked,mut col) = (String::new(),0,0);
+      for (i,c) in linetext.char_indices() {
+        let w = if c=='\t' && tab_width>0 {(col/tab_width+1)*tab_width-col} else {1};
+        if linestart+i<start {
+          if c=='\t' && tab_width==0 {before.push('\t');} else {before.push_str(&" ".repeat(w));}
+        }
+        else if linestart+i<end {marked+=w;}
+        col += w;
+      }
+      if marked==0 && (line==firstline || line==lastline) {marked=1;}
+      let _ = writeln!(out,"{}{:>width$} |{} {}",blue,line,reset,shown,width=width);
+      if marked>0 {
+        let _ = writeln!(out,"{} {}|{} {}{}{}{}",pad,blue,reset,before,sev,"^".repeat(marked),reset);
+      }
+      linestart = next;
+    }
+    if shownlast<lastline {
+      let _ = writeln!(out,"{}{}...{}",pad,blue,reset);
+    }
+    for note in &self.notes {
+      let _ = writeln!(out,"{} {}={} {}note{}: {}",pad,blue,reset,bold,reset,note);
+    }
+    out
+  }//render_lines
+
+  /// prints the diagnostic about the given source to stderr, with colors
+  /// if stderr is a terminal and the `NO_COLOR` environment variable is
+  /// not set
+  pub fn emit(&self, source:&LexSource)
+  {
+    eprint!("{}",self.render(source,use_color()));
+  }
+  /// prints the diagnostic to stderr, where its range is given by a [Span]
+  /// of a [SourceMap] instead of the fields start and end.  Nothing is
+  /// printed if the span is not in the map.
+  pub fn emit_in(&self, map:&SourceMap, span:Span)
+  {
+    if let Some((id,start)) = map.local(span.start) {
+      let source = map.get(id).unwrap();
+      let mut diag = self.clone();
+      diag.start = start;
+      diag.end = start+span.len();
+      diag.emit(source);
+    }
+  }
+}//impl Diagnostic
+
+fn use_color() -> bool
+{
+  std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+// the text with tabs expanded to tab stops of the given width, unless it
+// is zero, along with its width in characters
+fn expand_tabs(text:&str, tab_width:usize) -> (String,usize)
+{
+  let (mut shown,mut width) = (String::new(),0);
+  for c in text.chars() {
+    if c=='\t' && tab_width>0 {
+      let next = (width/tab_width+1)*tab_width;
+      shown.push_str(&" ".repeat(next-width));
+      width = next;
+    }
+    else { shown.push(c); width+=1; }
+  }
+  (shown,width)
+}
+
+// largest char boundary of text that is not greater than i
+fn floor_boundary(text:&str, i:usize) -> usize
+{
+  let mut i = i.min(text.len());
+  while !text.is_char_boundary(i) {i-=1;}
+  i
+}
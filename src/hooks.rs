@@ -0,0 +1,121 @@
+//! Callbacks that scan tokens with hand-written code.
+use std::fmt;
+use std::sync::Arc;
+use crate::{RawToken,TokenKind,LexerDef,StrTokenizer};
+
+/// The type of the callbacks registered with [LexerDef::add_hook].  A hook
+/// returns a token along with the number of bytes of input it consumed,
+/// or None to let the tokenizer scan the input as usual.
+pub type LexHookFn = dyn for<'a> Fn(&HookContext<'a>) -> Option<(RawToken<'a>,usize)> + Send + Sync;
+
+/// The input and position passed to a hook registered with
+/// [LexerDef::add_hook].
+pub struct HookContext<'a>
+{
+   input: &'a str,
+   position: usize,
+   line: usize,
+   column: usize,
+   previous: Option<(TokenKind,&'a str)>,
+}
+impl<'a> HookContext<'a>
+{
+  pub(crate) fn new(input:&'a str, position:usize, line:usize, column:usize,
+                    previous:Option<(TokenKind,&'a str)>) -> HookContext<'a>
+  {
+    HookContext{input,position,line,column,previous}
+  }
+  /// the whole input of the tokenizer
+  pub fn input(&self) -> &'a str {self.input}
+  /// the input from the current position, which starts with the prefix
+  /// of the hook
+  pub fn rest(&self) -> &'a str {&self.input[self.position..]}
+  /// the byte position in the input at which the hook is invoked
+  pub fn position(&self) -> usize {self.position}
+  /// the current line number
+  pub fn line(&self) -> usize {self.line}
+  /// the current column number, in bytes
+  pub fn column(&self) -> usize {self.column}
+  /// the kind and text of the previous token that is not trivia, as
+  /// returned by [StrTokenizer::previous], or None at the start of the
+  /// input.  Hooks can use it for rules that depend on context.
+  ///
+  /// Example:
+  ///```ignore
+  ///  // "#name" is a single alphanumeric token after ".", as in "this.#count"
+  ///  def.add_hook("#", |cx| match cx.previous() {
+  ///    Some((TokenKind::Symbol,".")) => {
+  ///      let rest = cx.rest();
+  ///      let len = rest[1..].find(|c:char| !c.is_alphanumeric() && c!='_').map_or(rest.len(),|i|i+1);
+  ///      Some((RawToken::Alphanum(&rest[..len]), len))
+  ///    },
+  ///    _ => None,
+  ///  });
+  ///```
+  pub fn previous(&self) -> Option<(TokenKind,&'a str)> {self.previous}
+}
+
+// a hook with the prefix that triggers it
+#[derive(Clone)]
+pub(crate) struct LexHook<'t>
+{
+   pub(crate) prefix: &'t str,
+   pub(crate) hook: Arc<LexHookFn>,
+}
+impl<'t> fmt::Debug for LexHook<'t>
+{
+  fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result
+  {
+    write!(f,"LexHook({:?})",self.prefix)
+  }
+}
+
+// invokes the hooks whose prefixes start the rest of the input, in order,
+// and returns the first token produced
+pub(crate) fn run_hooks<'a>(hooks:&[LexHook], cx:&HookContext<'a>) -> Option<(RawToken<'a>,usize)>
+{
+  let rest = cx.rest();
+  hooks.iter()
+    .filter(|h| rest.starts_with(h.prefix))
+    .find_map(|h| (h.hook)(cx).filter(|(_,len)| *len>0))
+}
+
+impl<'t> LexerDef<'t>
+{
+  /// registers a hook that is invoked whenever the input at the current
+  /// position starts with the given prefix, which can be a single
+  /// character, after whitespaces are skipped and before anything else is
+  /// recognized, including comments.  The hook receives a [HookContext],
+  /// and returns a token and the number of bytes it consumed, or None if
+  /// the input should be scanned as usual.  Hooks are tried in the order
+  /// in which they are registered.  A consumed length of zero is treated
+  /// as None; a length that does not end on a character boundary within
+  /// the input results in a [RawToken::LexError].
+  ///
+  /// Example:
+  ///```ignore
+  ///  // Markdown code fences as Verbatim tokens
+  ///  let mut def = LexerDef::new();
+  ///  def.add_hook("```", |cx| {
+  ///    let rest = cx.rest();
+  ///    let len = rest[3..].find("```").map(|i| i+6).unwrap_or(rest.len());
+  ///    Some((RawToken::Verbatim(&rest[..len]), len))
+  ///  });
+  ///```
+  pub fn add_hook<F>(&mut self, prefix:&'t str, hook:F)
+    where F: for<'a> Fn(&HookContext<'a>) -> Option<(RawToken<'a>,usize)> + Send + Sync + 'static
+  {
+    self.hooks.push(LexHook{prefix, hook:Arc::new(hook)});
+  }
+}
+
+impl<'t> StrTokenizer<'t>
+{
+  /// registers a hook that scans tokens with hand-written code, see
+  /// [LexerDef::add_hook]
+  pub fn add_hook<F>(&mut self, prefix:&'t str, hook:F)
+    where F: for<'a> Fn(&HookContext<'a>) -> Option<(RawToken<'a>,usize)> + Send + Sync + 'static
+  {
+    self.def_mut().add_hook(prefix,hook);
+  }
+}
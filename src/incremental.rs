@@ -0,0 +1,202 @@
+//! Incremental re-tokenization of edited text.
+use std::ops::Range;
+use crate::{OwnedToken,StrTokenizer,LexerDef,LexState,TokenKind};
+
+// An edit can change a token that ends before it only through the bytes
+// that the tokenizer examines past the end of the token.  There are at most
+// 5 of them after a single quote that may start a char literal (a 4-byte
+// character and the closing quote), 2 after an integer that may start a
+// float or a hexadecimal number ("1." or "0x" followed by a digit), and 1
+// after a symbol that may start a double or an identifier, so 8 leaves a
+// margin.  Comment delimiters and hook prefixes, which can be longer, are
+// added to the bound.
+const LOOKAHEAD:usize = 8;
+
+/// A token recorded by [IncrementalLexer], with its position in the text
+/// and the state of the tokenizer after it.
+#[derive(Debug,Clone)]
+pub struct LexedToken
+{
+   /// the token
+   pub token: OwnedToken,
+   /// line number of the start of the token
+   pub line: usize,
+   /// column number (in bytes) of the start of the token
+   pub column: usize,
+   /// byte position of the start of the token
+   pub start: usize,
+   /// byte position after the end of the token
+   pub end: usize,
+   /// state of the tokenizer after the token, from which it can resume
+   pub after: LexState,
+   line_start: usize, // start of the line of the token
+}
+
+/// An edit of a text, where the bytes from `start` to `old_end` of the old
+/// text were replaced by the bytes from `start` to `new_end` of the new text.
+#[cfg_attr(feature = "serde", derive(serde::Serialize,serde::Deserialize))]
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct TextEdit
+{
+   /// byte position of the start of the edit, in both texts
+   pub start: usize,
+   /// byte position of the end of the replaced range in the old text
+   pub old_end: usize,
+   /// byte position of the end of the inserted text in the new text
+   pub new_end: usize,
+}
+
+/// Keeps the tokens of a text that is edited repeatedly, as in an editor or
+/// language server, and re-tokenizes only the part of the text affected by
+/// each edit.  After an edit, tokenizing resumes from the state after the
+/// last token that ends before the edit, and stops as soon as a token after
+/// the edit is identical to an old token, from which point the old tokens
+/// are kept, with their positions shifted.
+///
+/// The tokenizer is configured by a [LexerDef] and the `keep` flags.  Unlike
+/// [StrTokenizer::set_input], trailing whitespaces are not trimmed from the
+/// text, column numbers are counted in bytes, and tokenizer errors are
+/// returned as [OwnedToken::LexError]s without being printed.
+///
+/// Example:
+///```ignore
+///  let def = LexerDef::new();
+///  let mut lexer = IncrementalLexer::new(&def);
+///  let mut text = String::from("x = 1;\ny = 2;");
+///  lexer.lex(&text);
+///  text.replace_range(4..5, "100");   // x = 100;
+///  let changed = lexer.edit(&text, TextEdit{start:4, old_end:5, new_end:7});
+///  for t in &lexer.tokens()[changed] { println!("{:?}",t.token); }
+///```
+pub struct IncrementalLexer<'d>
+{
+   def: &'d LexerDef<'d>,
+   tokens: Vec<LexedToken>,
+   /// flag that has the same meaning as [StrTokenizer::keep_whitespace]
+   pub keep_whitespace: bool,
+   /// flag that has the same meaning as [StrTokenizer::keep_newline]
+   pub keep_newline: bool,
+   /// flag that has the same meaning as [StrTokenizer::keep_comment]
+   pub keep_comment: bool,
+}
+impl<'d> IncrementalLexer<'d>
+{
+  /// creates an incremental lexer with no tokens
+  pub fn new(def:&'d LexerDef<'d>) -> IncrementalLexer<'d>
+  {
+    IncrementalLexer { def, tokens:Vec::new(), keep_whitespace:false, keep_newline:false, keep_comment:false }
+  }
+  /// the tokens of the text
+  pub fn tokens(&self) -> &[LexedToken] {&self.tokens}
+
+  fn tokenizer<'t>(&self, text:&'t str, state:LexState) -> StrTokenizer<'t> where 'd:'t
+  {
+    let mut stk = StrTokenizer::with_def(self.def);
+    stk.set_untrimmed_input(text);
+    stk.restore(state);
+    stk.set_quiet(true);
+    stk.keep_whitespace = self.keep_whitespace;
+    stk.keep_newline = self.keep_newline;
+    stk.keep_comment = self.keep_comment;
+    stk
+  }
+
+  /// tokenizes the whole text, replacing all tokens
+  pub fn lex(&mut self, text:&str)
+  {
+    let mut stk = self.tokenizer(text,LexState::start());
+    self.tokens.clear();
+    while let Some(t) = next_lexed(&mut stk) { self.tokens.push(t); }
+  }
+
+  /// updates the tokens after an edit, given the new text.  Returns the
+  /// range of indices of the new tokens that were tokenized again; the
+  /// tokens before the range are unchanged, and those after it are the old
+  /// tokens with their positions updated.
+  pub fn edit(&mut self, text:&str, edit:TextEdit) -> Range<usize>
+  {
+    // tokens that end well before the edit are not affected (the characters
+    // following a token, up to LOOKAHEAD bytes, can determine its end)
+    let lookahead = LOOKAHEAD.max(self.def.longest_prefix());
+    let keep = self.tokens.partition_point(|t| t.end+lookahead<=edit.start);
+    let state = if keep>0 {self.tokens[keep-1].after} else {LexState::start()};
+    let delta = edit.new_end as isize - edit.old_end as isize;
+    let shift = |p:usize| (p as isize + delta) as usize;
+    // first old token that starts after the edit
+    let mut old = self.tokens.partition_point(|t| t.start<edit.old_end);
+    let mut stk = self.tokenizer(text,state);
+    let mut relexed = Vec::new();
+    let mut resync = None;
+    while let Some(t) = next_lexed(&mut stk) {
+      if t.start>=edit.new_end {
+        while old<self.tokens.len() && shift(self.tokens[old].start)<t.start {old+=1;}
+        if let Some(o) = self.tokens.get(old) {
+          // the previous token must also be the same, since it can
+          // determine the tokens that follow, as with regex literals
+          if shift(o.start)==t.start && shift(o.end)==t.end
+             && shift_previous(o.after.previous,&edit)==Some(t.after.previous) {
+            resync = Some((old, t.line as isize - o.line as isize));
+            break;
+          }
+        }
+      }
+      relexed.push(t);
+    }//while
+    let changed = keep..keep+relexed.len();
+    let mut tail = Vec::new();
+    if let Some((old,linedelta)) = resync {
+      for mut t in self.tokens.drain(old..) {
+        // columns change only on lines that start in or before the edit
+        if t.line_start<=edit.old_end {
+          let pos = shift(t.line_start+t.column-1);
+          t.line_start = line_start_before(text,pos);
+          t.column = pos-t.line_start+1;
+        }
+        else {t.line_start = shift(t.line_start);}
+        t.start = shift(t.start);
+        t.end = shift(t.end);
+        t.line = (t.line as isize + linedelta) as usize;
+        t.after.position = shift(t.after.position);
+        t.after.line = (t.after.line as isize + linedelta) as usize;
+        t.after.line_start = if t.after.line_start<=edit.old_end
+          {line_start_before(text,t.after.position)} else {shift(t.after.line_start)};
+        if t.after.error_span.0>=edit.old_end {
+          t.after.error_span = (shift(t.after.error_span.0),shift(t.after.error_span.1));
+        }
+        t.after.previous = shift_previous(t.after.previous,&edit).flatten();
+        tail.push(t);
+      }
+    }
+    self.tokens.truncate(keep);
+    self.tokens.extend(relexed);
+    self.tokens.extend(tail);
+    changed
+  }//edit
+}//impl IncrementalLexer
+
+// the next token of the tokenizer, recorded with its position
+fn next_lexed(stk:&mut StrTokenizer) -> Option<LexedToken>
+{
+  let (token,line,column,start,line_start) = stk.next_token_at()?;
+  let after = stk.checkpoint();
+  Some(LexedToken { token:token.into_owned(), line, column, start, end:after.position(), after, line_start })
+}
+
+// the span of a previous token of the old text in the new text, or None if
+// the token was changed by the edit
+fn shift_previous(previous:Option<(TokenKind,usize,usize)>, edit:&TextEdit) -> Option<Option<(TokenKind,usize,usize)>>
+{
+  let delta = edit.new_end as isize - edit.old_end as isize;
+  match previous {
+    Some((kind,start,end)) if start>=edit.old_end =>
+      Some(Some((kind,(start as isize+delta) as usize,(end as isize+delta) as usize))),
+    Some((_,_,end)) if end>edit.start => None,
+    p => Some(p),
+  }
+}
+
+// the start of the line that contains byte position pos of text
+fn line_start_before(text:&str, pos:usize) -> usize
+{
+  text[..pos].rfind(['\n','\r']).map(|i|i+1).unwrap_or(0)
+}
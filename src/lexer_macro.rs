@@ -0,0 +1,269 @@
+//! The [lexer!](crate::lexer!) macro for defining lexers at compile time.
+
+use crate::{StringRule,TokenClass,TokenKind};
+
+/// Defines a lexer type at compile time.  The macro takes the name of the
+/// type to be defined followed by optional sections, in the following order,
+/// for single-character symbols, double-character symbols, keywords,
+/// comment markers, string literals and token classes:
+///```ignore
+///  basic_lexer::lexer! {
+///    /// lexer for a small C-like language
+///    pub struct CLexer;
+///    singles: [';', ',', '(', ')', '{', '}'];
+///    doubles: ["==", "!=", "<=", ">=", "&&", "||"];
+///    keywords: ["if", "else", "while", "return"];
+///    line_comment: "//";
+///    multiline_comment: "/*", "*/";
+///    strings: [("\"", "\"", Backslash), ("'", "'", Doubled)];
+///    classes: [(Alphanum, "a-zA-Z_$", "a-zA-Z0-9_$"), (Symbol, "+\\-*/=<>!&|", "+\\-*/=<>!&|")];
+///  }
+///  let mut lexer = CLexer::new("while (x<=10) x = x+1;");
+///  while let Some((token,line,column)) = lexer.next() {
+///    println!("{:?} at line {}, column {}",token,line,column);
+///  }
+///```
+/// Omitted single symbols and comment sections take the defaults of
+/// [StrTokenizer](crate::StrTokenizer) (the brackets `( ) [ ] { }`, "//"
+/// and "/\* \*/"); an empty string disables a comment marker.
+/// Strings are given by their start and end delimiters and an
+/// [Escape](crate::Escape), and default to `"` with backslash escapes.
+/// Token classes are given by a [TokenKind](crate::TokenKind) and the
+/// [CharSet](crate::CharSet)s of their first and following characters, and
+/// default to [DEFAULT_CLASSES](crate::DEFAULT_CLASSES).
+///
+/// The generated type `CLexer<'t>` contains the rules as associated
+/// constants `SINGLES`, `DOUBLES`, `KEYWORDS`, `LINE_COMMENT`,
+/// `MULTILINE_COMMENT`, `STRINGS` and `CLASSES`, from which a static
+/// [StateTable](crate::StateTable) is built at compile time.  The lexer is a
+/// [TableLexer](crate::TableLexer) over this table, so creating one neither
+/// compiles regular expressions nor allocates memory.  The type implements
+/// [Iterator] with the same items as [StrTokenizer](crate::StrTokenizer),
+/// and dereferences to the TableLexer for its other functions and flags.
+///
+/// The rules are checked when the program is compiled.  Duplicated
+/// symbols and keywords, double-character symbols that are not two ascii
+/// characters, symbols given as both single and double, keywords that are
+/// not alphanumeric, token classes of other kinds than Alphanum, Symbol
+/// and Verbatim, and symbols that can never be returned because they are
+/// hidden by whitespaces, comment markers, string delimiters or
+/// alphanumeric tokens are all reported as compile-time errors.
+#[macro_export]
+macro_rules! lexer {
+  (@or $default:expr) => { $default };
+  (@or $default:expr, $given:expr) => { $given };
+  (
+    $(#[$meta:meta])*
+    $vis:vis struct $name:ident;
+    $(singles: [$($single:expr),* $(,)?];)?
+    $(doubles: [$($double:expr),* $(,)?];)?
+    $(keywords: [$($keyword:expr),* $(,)?];)?
+    $(line_comment: $lc:expr;)?
+    $(multiline_comment: $mls:expr, $mle:expr;)?
+    $(strings: [$(($ss:expr, $se:expr, $esc:ident)),* $(,)?];)?
+    $(classes: [$(($kind:ident, $cs:expr, $cc:expr)),* $(,)?];)?
+  ) => {
+    $(#[$meta])*
+    $vis struct $name<'t> {
+      lexer: $crate::TableLexer<'static,'t>,
+    }
+    #[allow(dead_code)]
+    impl<'t> $name<'t> {
+      /// single-character symbols
+      pub const SINGLES: &'static [char] = $crate::lexer!(@or &['(',')','[',']','{','}'] $(, &[$($single),*])?);
+      /// double-character symbols
+      pub const DOUBLES: &'static [&'static str] = &[$($($double),*)?];
+      /// keywords
+      pub const KEYWORDS: &'static [&'static str] = &[$($($keyword),*)?];
+      /// symbol that starts a line comment, empty if disabled
+      pub const LINE_COMMENT: &'static str = $crate::lexer!(@or "//" $(, $lc)?);
+      /// delimiters of multi-line comments, empty if disabled
+      pub const MULTILINE_COMMENT: (&'static str, &'static str) =
+        ($crate::lexer!(@or "/*" $(, $mls)?), $crate::lexer!(@or "*/" $(, $mle)?));
+      /// kinds of string literals
+      pub const STRINGS: &'static [$crate::StringRule<'static>] = $crate::lexer!(@or
+        &[$crate::StringRule{start:"\"", end:"\"", escape:$crate::Escape::Backslash}]
+        $(, &[$($crate::StringRule{start:$ss, end:$se, escape:$crate::Escape::$esc}),*])?);
+      /// token classes, the default classes if empty
+      pub const CLASSES: &'static [$crate::TokenClass<'static>] = &[$($(
+        $crate::TokenClass{kind:$crate::TokenKind::$kind, start:$crate::CharSet::new($cs), cont:$crate::CharSet::new($cc)}
+      ),*)?];
+
+      /// the static table of the single state of this lexer
+      pub fn states() -> &'static [$crate::StateTable<'static>] {
+        static STATES: &[$crate::StateTable<'static>] = &[$crate::StateTable {
+          name: stringify!($name),
+          singles: ::std::borrow::Cow::Borrowed(<$name<'static>>::SINGLES),
+          doubles: ::std::borrow::Cow::Borrowed(<$name<'static>>::DOUBLES),
+          keywords: ::std::borrow::Cow::Borrowed(<$name<'static>>::KEYWORDS),
+          line_comment: <$name<'static>>::LINE_COMMENT,
+          multiline_comment: <$name<'static>>::MULTILINE_COMMENT,
+          strings: ::std::borrow::Cow::Borrowed(<$name<'static>>::STRINGS),
+          classes: ::std::borrow::Cow::Borrowed(<$name<'static>>::CLASSES),
+          transitions: ::std::borrow::Cow::Borrowed(&[]),
+          numbers: $crate::NumberFormat{hex:true, binary:false, octal:false, exponents:false, underscores:false},
+          text: false,
+        }];
+        STATES
+      }
+      /// creates a lexer for the given input, without allocating memory
+      pub fn new(input: &'t str) -> Self {
+        $name { lexer: $crate::TableLexer::new(Self::states(), input) }
+      }
+    }
+    const _: () = $crate::check_lexer_rules(
+      <$name<'static>>::SINGLES, <$name<'static>>::DOUBLES,
+      <$name<'static>>::KEYWORDS, <$name<'static>>::LINE_COMMENT,
+      <$name<'static>>::MULTILINE_COMMENT, <$name<'static>>::STRINGS,
+      <$name<'static>>::CLASSES);
+    impl<'t> ::std::ops::Deref for $name<'t> {
+      type Target = $crate::TableLexer<'static,'t>;
+      fn deref(&self) -> &Self::Target { &self.lexer }
+    }
+    impl<'t> ::std::ops::DerefMut for $name<'t> {
+      fn deref_mut(&mut self) -> &mut Self::Target { &mut self.lexer }
+    }
+    impl<'t> ::std::iter::Iterator for $name<'t> {
+      type Item = ($crate::RawToken<'t>, usize, usize);
+      fn next(&mut self) -> Option<Self::Item> { self.lexer.next_token() }
+    }
+  };
+}//lexer!
+
+const fn str_eq(a:&str, b:&str) -> bool
+{
+  let (a,b) = (a.as_bytes(),b.as_bytes());
+  if a.len()!=b.len() {return false;}
+  let mut i = 0;
+  while i<a.len() { if a[i]!=b[i] {return false;} i+=1; }
+  true
+}
+const fn starts_with(s:&str, prefix:&str) -> bool
+{
+  let (s,p) = (s.as_bytes(),prefix.as_bytes());
+  if p.is_empty() || p.len()>s.len() {return false;}
+  let mut i = 0;
+  while i<p.len() { if s[i]!=p[i] {return false;} i+=1; }
+  true
+}
+// whether s is made of a character of the start set of the class followed
+// by characters of its continue set.  Non-ascii characters cannot be looked
+// up in constant expressions, and are taken to be in the sets if non_ascii
+// is true.
+const fn in_class(s:&str, class:&TokenClass, non_ascii:bool) -> bool
+{
+  let s = s.as_bytes();
+  if s.is_empty() || !((s[0]>=128 && non_ascii) || class.start.contains_ascii(s[0])) {return false;}
+  let mut i = 1;
+  while i<s.len() {
+    if !((s[i]>=128 && non_ascii) || class.cont.contains_ascii(s[i])) {return false;}
+    i+=1;
+  }
+  true
+}
+// whether s is an alphanumeric token, given the classes of the lexer,
+// which are the default classes if there are none
+const fn is_alphanum(s:&str, classes:&[TokenClass], non_ascii:bool) -> bool
+{
+  let classes = if classes.is_empty() {&crate::DEFAULT_CLASSES} else {classes};
+  let mut k = 0;
+  while k<classes.len() {
+    if matches!(classes[k].kind,TokenKind::Alphanum) && in_class(s,&classes[k],non_ascii) {return true;}
+    k+=1;
+  }
+  false
+}
+const fn has_duplicates(v:&[&str]) -> bool
+{
+  let mut i = 0;
+  while i<v.len() {
+    let mut j = i+1;
+    while j<v.len() { if str_eq(v[i],v[j]) {return true;} j+=1; }
+    i+=1;
+  }
+  false
+}
+// whether a symbol that starts with s is hidden by a rule that the lexer
+// tries before symbols: a comment marker or a string delimiter
+const fn is_hidden(s:&str, line_comment:&str, mls:&str, strings:&[StringRule]) -> bool
+{
+  if starts_with(s,line_comment) || starts_with(s,mls) {return true;}
+  let mut k = 0;
+  while k<strings.len() {
+    if starts_with(s,strings[k].start) {return true;}
+    k+=1;
+  }
+  false
+}
+
+/// Checks the rules given to [lexer!](crate::lexer!), panicking if they
+/// conflict or if some rule cannot be reached.  Called in a constant
+/// context by the macro, so that the panics become compile-time errors.
+#[doc(hidden)]
+pub const fn check_lexer_rules(singles:&[char], doubles:&[&str], keywords:&[&str], line_comment:&str,
+                               multiline_comment:(&str,&str), strings:&[StringRule], classes:&[TokenClass])
+{
+  let (mls,mle) = multiline_comment;
+  if mls.is_empty() != mle.is_empty() {
+    panic!("lexer!: multiline_comment requires both a start and an end symbol");
+  }
+  let mut k = 0;
+  while k<strings.len() {
+    if strings[k].start.is_empty() || strings[k].end.is_empty() {
+      panic!("lexer!: strings require a start and an end delimiter");
+    }
+    k+=1;
+  }
+  k = 0;
+  while k<classes.len() {
+    if !matches!(classes[k].kind,TokenKind::Alphanum|TokenKind::Symbol|TokenKind::Verbatim) {
+      panic!("lexer!: token classes must be Alphanum, Symbol or Verbatim");
+    }
+    k+=1;
+  }
+  let mut i = 0;
+  while i<singles.len() {
+    let c = singles[i];
+    if c.is_whitespace() {panic!("lexer!: whitespace single symbols are unreachable");}
+    let mut j = i+1;
+    while j<singles.len() {
+      if singles[j]==c {panic!("lexer!: duplicate single symbol");}
+      j+=1;
+    }
+    let mut buf = [0u8;4];
+    let cs:&str = c.encode_utf8(&mut buf);
+    if is_hidden(cs,line_comment,mls,strings) {
+      panic!("lexer!: single symbol is unreachable because it starts a comment or a string");
+    }
+    if is_alphanum(cs,classes,false) {
+      panic!("lexer!: single symbol conflicts with the alphanumeric tokens that start with it");
+    }
+    j = 0;
+    while j<doubles.len() {
+      if str_eq(cs,doubles[j]) {panic!("lexer!: symbol is both a single and a double symbol");}
+      j+=1;
+    }
+    i+=1;
+  }
+  if has_duplicates(doubles) {panic!("lexer!: duplicate double symbol");}
+  i = 0;
+  while i<doubles.len() {
+    let d = doubles[i].as_bytes();
+    if d.len()!=2 || !d[0].is_ascii() || !d[1].is_ascii() {
+      panic!("lexer!: double symbols must be two ascii characters");
+    }
+    if d[0].is_ascii_whitespace() || d[1].is_ascii_whitespace() {
+      panic!("lexer!: double symbols that contain whitespaces are unreachable");
+    }
+    if is_hidden(doubles[i],line_comment,mls,strings) {
+      panic!("lexer!: double symbol is unreachable because it starts with a comment marker or a string delimiter");
+    }
+    i+=1;
+  }
+  if has_duplicates(keywords) {panic!("lexer!: duplicate keyword");}
+  i = 0;
+  while i<keywords.len() {
+    if !is_alphanum(keywords[i],classes,true) {panic!("lexer!: keywords must be alphanumeric");}
+    i+=1;
+  }
+}//check_lexer_rules
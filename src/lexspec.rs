@@ -0,0 +1,433 @@
+//! Declarative lexer specifications.
+
+use std::io::{self,Write,ErrorKind};
+use std::borrow::Cow;
+use crate::{StrTokenizer,LexerDef,NumberFormat,TokenKind};
+use crate::{CharSet,Escape,StateTable,StringRule,TableLexer,TokenClass,Transition};
+
+/// A lexer specification (conventionally a *.lex* file) describes the
+/// rules of a lexer one directive per line, with whitespace-separated
+/// arguments.  Lines that begin with `#` are comments.
+///```text
+///  # lexer for a small C-like language with template strings
+///  line_comment //
+///  multiline_comment /* */
+///  singles ; ,
+///  doubles == != <= >= && || ++ --
+///  keywords if else while for return
+///  string " " backslash
+///  string ' ' backslash
+///  class alphanum a-zA-Z_$ a-zA-Z0-9_$
+///  numbers hex exponents underscores
+///  goto template `
+///  keep newline comment
+///
+///  state template
+///  text
+///  goto main `
+///```
+/// The recognized directives are:
+/// - `line_comment` *symbol* : the symbol that starts a single-line comment.
+///   Without an argument, line comments are disabled.
+/// - `multiline_comment` *start end* : the delimiters of multi-line
+///   comments.  Without arguments, multi-line comments are disabled.
+/// - `singles` *c1 c2 ...* : single-character symbols, in addition to
+///   the brackets `( ) [ ] { }` in the initial state.
+/// - `doubles` *s1 s2 ...* : two-character symbols.
+/// - `keywords` *k1 k2 ...* : alphanumeric keywords.
+/// - `string` *start end* \[*backslash|doubled|raw*\] : a kind of string
+///   literal and how its end delimiter is escaped, by default with a
+///   backslash.  The first `string` of a state replaces the default `" "`.
+/// - `class` *alphanum|symbol|verbatim start* \[*continue*\] : a class of
+///   tokens made of a character of the set *start* followed by characters
+///   of the set *continue* (by default *start*), given as ranges such as
+///   `a-zA-Z_`, see [CharSet].  The first `class` of a state replaces the
+///   default classes, see [DEFAULT_CLASSES](crate::DEFAULT_CLASSES).
+/// - `numbers` \[*hex binary octal exponents underscores*\] : the numeric
+///   literals recognized in addition to decimal ones, see [NumberFormat].
+/// - `state` *name* : starts a new lexer state, to which the following
+///   directives apply.  The directives before the first `state` apply to
+///   the initial state `main`.  A new state has no comments, strings or
+///   symbols.
+/// - `goto` *state s1 s2 ...* : symbols that switch to the given state.
+/// - `text` : the state returns everything up to the next symbol of its
+///   `goto`s as a single [Verbatim](crate::RawToken::Verbatim) token.
+/// - `keep` *whitespace|newline|comment ...* : token types to keep, in all
+///   states.
+///
+/// Directives other than `line_comment`, `multiline_comment` and `numbers`
+/// can be repeated.  A [LexSpec] can be compiled by [compile_lexspec]
+/// (usually from a *build.rs* script) into a Rust module containing the
+/// static tables of a [TableLexer], or loaded at runtime and used with a
+/// TableLexer through [LexSpec::tables].  The initial state of a
+/// specification can also configure a [StrTokenizer], see
+/// [LexSpec::configure].
+#[derive(Clone,Debug,PartialEq)]
+pub struct LexSpec
+{
+   /// the states of the lexer, starting with the initial state
+   pub states: Vec<StateSpec>,
+   /// whether whitespaces are returned as tokens
+   pub keep_whitespace: bool,
+   /// whether newlines are returned as tokens
+   pub keep_newline: bool,
+   /// whether comments are returned as tokens
+   pub keep_comment: bool,
+}
+impl Default for LexSpec
+{
+  fn default() -> Self
+  {
+    let mut main = StateSpec::new("main");
+    main.singles = vec!['(',')','[',']','{','}'];
+    main.line_comment = String::from("//");
+    main.multiline_comment = (String::from("/*"),String::from("*/"));
+    main.strings.push((String::from("\""),String::from("\""),Escape::Backslash));
+    LexSpec { states: vec![main], keep_whitespace: false, keep_newline: false, keep_comment: false }
+  }
+}
+
+/// The rules of one state of a [LexSpec], see [StateTable] for their
+/// meaning
+#[derive(Clone,Debug,PartialEq)]
+pub struct StateSpec
+{
+   /// name of the state
+   pub name: String,
+   /// symbol that starts a single-line comment, empty if disabled
+   pub line_comment: String,
+   /// start and end delimiters of multi-line comments, empty if disabled
+   pub multiline_comment: (String,String),
+   /// single-character symbols
+   pub singles: Vec<char>,
+   /// two-character symbols
+   pub doubles: Vec<String>,
+   /// keywords
+   pub keywords: Vec<String>,
+   /// string literals, as start and end delimiters and escape
+   pub strings: Vec<(String,String,Escape)>,
+   /// token classes, as kind, start and continue sets
+   pub classes: Vec<(TokenKind,String,String)>,
+   /// symbols that switch states, with the name of the state
+   pub transitions: Vec<(String,String)>,
+   /// numeric literals
+   pub numbers: NumberFormat,
+   /// whether the state returns text up to its next transition
+   pub text: bool,
+}
+impl StateSpec
+{
+  /// creates a state with the given name and no rules
+  pub fn new(name:&str) -> StateSpec
+  {
+    StateSpec {
+      name: name.to_owned(),
+      line_comment: String::new(),
+      multiline_comment: (String::new(),String::new()),
+      singles: Vec::new(),
+      doubles: Vec::new(),
+      keywords: Vec::new(),
+      strings: Vec::new(),
+      classes: Vec::new(),
+      transitions: Vec::new(),
+      numbers: NumberFormat::default(),
+      text: false,
+    }
+  }
+}
+
+fn spec_error(linenum:usize, msg:String) -> io::Error
+{
+  io::Error::new(ErrorKind::InvalidData, format!("lexer specification line {}: {}",linenum,msg))
+}
+
+impl LexSpec
+{
+  /// parses a lexer specification from a string.  Errors are returned as
+  /// [std::io::Error]s of kind [ErrorKind::InvalidData], with a message that
+  /// includes the offending line number.
+  pub fn parse(text:&str) -> io::Result<LexSpec>
+  {
+    let mut spec = LexSpec::default();
+    let mut cur = 0;  // index of the current state
+    // whether the current state has its own strings and classes yet
+    let (mut own_strings,mut own_classes) = (false,false);
+    let mut gotos = Vec::new();  // target states, with the line they are on
+    for (i,line) in text.lines().enumerate()
+    {
+      let linenum = i+1;
+      let mut words = line.split_whitespace();
+      let directive = match words.next() {
+        Some(d) if !d.starts_with('#') => d,
+        _ => continue,  // blank line or comment
+      };
+      let args:Vec<&str> = words.collect();
+      let state = &mut spec.states[cur];
+      match directive {
+        "line_comment" => {
+          if args.len()>1 {return Err(spec_error(linenum,String::from("line_comment takes at most one symbol")));}
+          state.line_comment = args.first().map(|s|s.to_string()).unwrap_or_default();
+        },
+        "multiline_comment" => {
+          match args.len() {
+            0 => { state.multiline_comment = (String::new(),String::new()); },
+            2 => { state.multiline_comment = (args[0].to_owned(),args[1].to_owned()); },
+            _ => {return Err(spec_error(linenum,String::from("multiline_comment requires a start and an end symbol")));},
+          }
+        },
+        "singles" => {
+          for a in args {
+            let mut chars = a.chars();
+            match (chars.next(),chars.next()) {
+              (Some(c),None) => state.singles.push(c),
+              _ => {return Err(spec_error(linenum,format!("\"{}\" is not a single character",a)));},
+            }
+          }
+        },
+        "doubles" => {
+          for a in args {
+            if a.len()!=2 {return Err(spec_error(linenum,format!("\"{}\" is not a two-character symbol",a)));}
+            state.doubles.push(a.to_owned());
+          }
+        },
+        "keywords" => { for a in args {state.keywords.push(a.to_owned());} },
+        "string" => {
+          let escape = match args.get(2) {
+            None | Some(&"backslash") => Escape::Backslash,
+            Some(&"doubled") => Escape::Doubled,
+            Some(&"raw") => Escape::Raw,
+            Some(e) => {return Err(spec_error(linenum,format!("unknown escape \"{}\" in string",e)));},
+          };
+          if args.len()<2 || args.len()>3 {
+            return Err(spec_error(linenum,String::from("string requires a start and an end delimiter")));
+          }
+          if !own_strings { state.strings.clear(); own_strings = true; }
+          state.strings.push((args[0].to_owned(),args[1].to_owned(),escape));
+        },
+        "class" => {
+          let kind = match args.first() {
+            Some(&"alphanum") => TokenKind::Alphanum,
+            Some(&"symbol") => TokenKind::Symbol,
+            Some(&"verbatim") => TokenKind::Verbatim,
+            _ => {return Err(spec_error(linenum,String::from("class requires a kind: alphanum, symbol or verbatim")));},
+          };
+          if args.len()<2 || args.len()>3 {
+            return Err(spec_error(linenum,String::from("class requires a start set and at most a continue set")));
+          }
+          if !own_classes { state.classes.clear(); own_classes = true; }
+          let cont = args.get(2).unwrap_or(&args[1]);
+          state.classes.push((kind,args[1].to_owned(),(*cont).to_owned()));
+        },
+        "numbers" => {
+          let mut f = NumberFormat{hex:false, binary:false, octal:false, exponents:false, underscores:false};
+          for a in args {
+            match a {
+              "hex" => {f.hex=true;},
+              "binary" => {f.binary=true;},
+              "octal" => {f.octal=true;},
+              "exponents" => {f.exponents=true;},
+              "underscores" => {f.underscores=true;},
+              _ => {return Err(spec_error(linenum,format!("unknown number format \"{}\"",a)));},
+            }
+          }
+          state.numbers = f;
+        },
+        "state" => {
+          if args.len()!=1 {return Err(spec_error(linenum,String::from("state requires a name")));}
+          if spec.states.iter().any(|s|s.name==args[0]) {
+            return Err(spec_error(linenum,format!("state \"{}\" is defined twice",args[0])));
+          }
+          spec.states.push(StateSpec::new(args[0]));
+          cur = spec.states.len()-1;
+          own_strings = false; own_classes = false;
+        },
+        "goto" => {
+          if args.len()<2 {return Err(spec_error(linenum,String::from("goto requires a state and symbols")));}
+          for a in &args[1..] { state.transitions.push(((*a).to_owned(),args[0].to_owned())); }
+          gotos.push((linenum,args[0]));
+        },
+        "text" => {
+          if !args.is_empty() {return Err(spec_error(linenum,String::from("text takes no arguments")));}
+          state.text = true;
+        },
+        "keep" => {
+          for a in args {
+            match a {
+              "whitespace" => {spec.keep_whitespace=true;},
+              "newline" => {spec.keep_newline=true;},
+              "comment" => {spec.keep_comment=true;},
+              _ => {return Err(spec_error(linenum,format!("unknown token type \"{}\" in keep",a)));},
+            }
+          }
+        },
+        _ => {return Err(spec_error(linenum,format!("unknown directive \"{}\"",directive)));},
+      }//match directive
+    }//for each line
+    for (linenum,target) in gotos {
+      if !spec.states.iter().any(|s|s.name==target) {
+        return Err(spec_error(linenum,format!("unknown state \"{}\"",target)));
+      }
+    }
+    Ok(spec)
+  }//parse
+
+  /// reads and parses a lexer specification from a file
+  pub fn from_file(path:&str) -> io::Result<LexSpec>
+  {
+    LexSpec::parse(&std::fs::read_to_string(path)?)
+  }
+
+  /// the initial state of the specification
+  pub fn initial(&self) -> &StateSpec {&self.states[0]}
+  /// the initial state of the specification, for changing its rules
+  pub fn initial_mut(&mut self) -> &mut StateSpec {&mut self.states[0]}
+
+  /// applies the initial state of this specification to an existing
+  /// tokenizer.  StrTokenizer has a single state and no token classes, so
+  /// only the comments, symbols, keywords, number format and the string
+  /// literals delimited by a single character, such as `" "`, are used.
+  pub fn configure<'t>(&'t self, stk:&mut StrTokenizer<'t>)
+  {
+    self.configure_def(stk.def_mut());
+    stk.keep_whitespace = self.keep_whitespace;
+    stk.keep_newline = self.keep_newline;
+    stk.keep_comment = self.keep_comment;
+  }
+  fn configure_def<'t>(&'t self, def:&mut LexerDef<'t>)
+  {
+    let main = self.initial();
+    def.set_line_comment(&main.line_comment);
+    def.set_multiline_comments("");
+    def.set_multiline_comment_delimiters(&main.multiline_comment.0,&main.multiline_comment.1);
+    for c in &main.singles {def.add_single(*c);}
+    for d in &main.doubles {def.add_double(d);}
+    for k in &main.keywords {def.add_keywords(k);}
+    def.set_number_format(main.numbers);
+    let delims:String = main.strings.iter().filter(|(s,e,_)| s==e && s.chars().count()==1)
+                                    .map(|(s,_,_)|s.as_str()).collect();
+    if let Some((_,_,escape)) = main.strings.first() { def.set_backslash_escapes(*escape==Escape::Backslash); }
+    if delims.contains('\'') { def.set_char_literals(false); }
+    def.set_string_delimiters(&delims);
+  }
+
+  /// creates a [LexerDef] with the rules of the initial state of this
+  /// specification (see [LexSpec::configure]), which can be shared by many
+  /// tokenizers.  Note that the `keep` flags are set on each tokenizer,
+  /// and are not part of the definition.
+  pub fn definition(&self) -> LexerDef<'_>
+  {
+    let mut def = LexerDef::new();
+    self.configure_def(&mut def);
+    def
+  }
+
+  /// creates a tokenizer configured by the initial state of this
+  /// specification, with the given input
+  pub fn tokenizer<'t>(&'t self, input:&'t str) -> StrTokenizer<'t>
+  {
+    let mut stk = StrTokenizer::from_str(input);
+    self.configure(&mut stk);
+    stk
+  }
+
+  /// returns the tables of the states of this specification, which borrow
+  /// from it, for a [TableLexer]:
+  ///```ignore
+  ///  let spec = LexSpec::from_file("mylang.lex")?;
+  ///  let tables = spec.tables();
+  ///  let mut lexer = TableLexer::new(&tables, input);
+  ///```
+  pub fn tables(&self) -> Vec<StateTable<'_>>
+  {
+    let index = |name:&str| self.states.iter().position(|s|s.name==name).unwrap_or(0);
+    self.states.iter().map(|s| StateTable {
+      name: &s.name,
+      singles: Cow::Borrowed(&s.singles),
+      doubles: Cow::Owned(s.doubles.iter().map(|d|d.as_str()).collect()),
+      keywords: Cow::Owned(s.keywords.iter().map(|k|k.as_str()).collect()),
+      line_comment: &s.line_comment,
+      multiline_comment: (&s.multiline_comment.0,&s.multiline_comment.1),
+      strings: s.strings.iter().map(|(start,end,escape)| StringRule{start,end,escape:*escape}).collect(),
+      classes: s.classes.iter().map(|(kind,start,cont)|
+                 TokenClass{kind:*kind, start:CharSet::new(start), cont:CharSet::new(cont)}).collect(),
+      transitions: s.transitions.iter().map(|(on,to)| Transition{on,to:index(to)}).collect(),
+      numbers: s.numbers,
+      text: s.text,
+    }).collect()
+  }
+
+  /// writes Rust source code for a module that contains the tables of
+  /// this specification as a static array `STATES` of [StateTable]s,
+  /// along with a function `make_tokenizer(input:&str) -> TableLexer`
+  /// that creates a [TableLexer] with the `keep` flags of the
+  /// specification.  The module depends only on this crate.
+  pub fn generate<W:Write>(&self, out:&mut W) -> io::Result<()>
+  {
+    let tables = self.tables();
+    writeln!(out,"// Generated by basic_lexer from a lexer specification, do not edit.")?;
+    writeln!(out,"#[allow(unused_imports)]")?;
+    writeln!(out,"use basic_lexer::{{CharSet, Escape, NumberFormat, StateTable, StringRule, TableLexer, TokenClass, TokenKind, Transition}};")?;
+    writeln!(out,"use std::borrow::Cow;")?;
+    writeln!(out)?;
+    writeln!(out,"/// the states of the lexer, starting with the initial state")?;
+    writeln!(out,"pub static STATES: &[StateTable<'static>] = &[")?;
+    for t in &tables {
+      writeln!(out,"    StateTable {{")?;
+      writeln!(out,"        name: {:?},",t.name)?;
+      writeln!(out,"        singles: Cow::Borrowed(&{:?}),",t.singles)?;
+      writeln!(out,"        doubles: Cow::Borrowed(&{:?}),",t.doubles)?;
+      writeln!(out,"        keywords: Cow::Borrowed(&{:?}),",t.keywords)?;
+      writeln!(out,"        line_comment: {:?},",t.line_comment)?;
+      writeln!(out,"        multiline_comment: {:?},",t.multiline_comment)?;
+      let strings:Vec<String> = t.strings.iter().map(|r|
+        format!("StringRule {{ start: {:?}, end: {:?}, escape: Escape::{:?} }}",r.start,r.end,r.escape)).collect();
+      let classes:Vec<String> = t.classes.iter().map(|k|
+        format!("TokenClass {{ kind: TokenKind::{:?}, start: CharSet::new({:?}), cont: CharSet::new({:?}) }}",
+                k.kind,k.start.as_str(),k.cont.as_str())).collect();
+      let transitions:Vec<String> = t.transitions.iter().map(|tr|
+        format!("Transition {{ on: {:?}, to: {} }}",tr.on,tr.to)).collect();
+      for (field,items) in [("strings",strings),("classes",classes),("transitions",transitions)] {
+        if items.is_empty() { writeln!(out,"        {}: Cow::Borrowed(&[]),",field)?; continue; }
+        writeln!(out,"        {}: Cow::Borrowed(&[",field)?;
+        for item in items { writeln!(out,"            {},",item)?; }
+        writeln!(out,"        ]),")?;
+      }
+      writeln!(out,"        numbers: {:?},",t.numbers)?;
+      writeln!(out,"        text: {},",t.text)?;
+      writeln!(out,"    }},")?;
+    }
+    writeln!(out,"];")?;
+    writeln!(out)?;
+    writeln!(out,"/// creates a lexer for the given input, driven by the static tables")?;
+    writeln!(out,"pub fn make_tokenizer(input: &str) -> TableLexer<'static, '_> {{")?;
+    writeln!(out,"    let mut lexer = TableLexer::new(STATES, input);")?;
+    writeln!(out,"    lexer.keep_whitespace = {};",self.keep_whitespace)?;
+    writeln!(out,"    lexer.keep_newline = {};",self.keep_newline)?;
+    writeln!(out,"    lexer.keep_comment = {};",self.keep_comment)?;
+    writeln!(out,"    lexer")?;
+    writeln!(out,"}}")?;
+    Ok(())
+  }//generate
+}//impl LexSpec
+
+/// Compiles the lexer specification at `spec_path` into a Rust module
+/// written to `out_path`, using [LexSpec::generate].  This function is
+/// intended to be called from a *build.rs* script:
+///```ignore
+///  // build.rs
+///  fn main() {
+///    let out = format!("{}/lexer.rs", std::env::var("OUT_DIR").unwrap());
+///    basic_lexer::compile_lexspec("src/mylang.lex", &out).unwrap();
+///    println!("cargo:rerun-if-changed=src/mylang.lex");
+///  }
+///  // in the crate
+///  mod lexer { include!(concat!(env!("OUT_DIR"), "/lexer.rs")); }
+///  let mut lexer = lexer::make_tokenizer("while (x<10) x++;");
+///```
+pub fn compile_lexspec(spec_path:&str, out_path:&str) -> io::Result<()>
+{
+  let spec = LexSpec::from_file(spec_path)?;
+  let mut out = io::BufWriter::new(std::fs::File::create(out_path)?);
+  spec.generate(&mut out)?;
+  out.flush()
+}
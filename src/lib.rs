@@ -18,8 +18,8 @@
 //! Example: given the *Cargo.toml* file of this crate,
 //!```ignore
 //!  let source = LexSource::new("Cargo.toml").unwrap();
-//!  let mut tokenizer = StrTokenizer::from_source(&source);
-//!  tokenizer.set_line_comment("#");
+//!  let mut tokenizer = StrTokenizer::for_language(Language::Toml);
+//!  tokenizer.set_input(source.get_contents());
 //!  tokenizer.keep_comment=true;
 //!  tokenizer.keep_newline=false;
 //!  tokenizer.keep_whitespace=false; 
@@ -28,7 +28,7 @@
 //!  } 
 //!```
 //! This code produces output
-//!```
+//!```text
 //! Token: (Symbol("["), 1, 1)
 //! Token: (Alphanum("package"), 1, 2) 
 //! Token: (Symbol("]"), 1, 9)
@@ -47,6 +47,20 @@
 //!```
 //! etc.. The numbers returned alongside each token represent the line and
 //! column positions of the start of the token.
+//! Presets for other common languages are selected with [Language], and
+//! any tokenizer can be configured by hand, as described for [LexerDef].
+//! Lexers can also be described declaratively, with states and token
+//! classes, in a [LexSpec] file that is compiled into the static tables
+//! of a [TableLexer], or with the [lexer!] macro.
+//!
+//! With the optional `rustlr` feature enabled, a `RustlrTokenizer` adapter
+//! implements rustlr's `Tokenizer` trait, so that a StrTokenizer can
+//! directly drive a parser generated by rustlr.
+//!
+//! The crate also builds a `basic_lexer` command that prints the tokens of
+//! files or standard input as text, JSON Lines or a table, with options for
+//! comment markers, operators, keywords and the `keep` flags, or a
+//! [LexSpec] file.  Run `basic_lexer --help` for usage.
 
 #![allow(dead_code)]
 #![allow(unused_variables)]
@@ -58,17 +72,46 @@
 #![allow(unused_assignments)]
 // #[doc(inline)]
 
-use std::io::{Read,Error,BufRead,BufReader,Lines,Result};
+use std::io::{Read,Error,BufRead,BufReader,Lines,Result,Seek,SeekFrom};
 use std::cell::{RefCell,Ref,RefMut};
 use std::rc::Rc;
 use std::fs::File;
-use std::collections::{HashMap,HashSet};
+use std::collections::{HashMap,HashSet,VecDeque};
 use crate::Token::*;
 
 mod zero_copy;
 pub use zero_copy::*;
+mod lexspec;
+pub use lexspec::*;
+mod table_lexer;
+pub use table_lexer::*;
+mod lexer_macro;
+pub use lexer_macro::check_lexer_rules;
+mod parallel;
+pub use parallel::*;
+mod stream;
+pub use stream::*;
+mod source_map;
+pub use source_map::*;
+mod diagnostic;
+pub use diagnostic::*;
+mod trivia;
+pub use trivia::*;
+mod incremental;
+pub use incremental::*;
+mod token_cache;
+pub use token_cache::*;
+mod presets;
+pub use presets::*;
+mod hooks;
+pub use hooks::{HookContext,LexHookFn};
+#[cfg(feature = "rustlr")]
+mod rustlr_interface;
+#[cfg(feature = "rustlr")]
+pub use rustlr_interface::*;
 
 /// Tokens are returned by the iterators [Str_tokenizer] and [File_tokenizer].
+#[cfg_attr(feature = "serde", derive(serde::Serialize,serde::Deserialize))]
 #[derive(Clone,PartialEq,Debug)]
 pub enum Token
 {
@@ -101,8 +144,61 @@ pub enum Token
    Newline,
    #[doc(hidden)]
    Nothing,
-   #[doc(hidden)]   
-   Error(&'static str),
+   #[doc(hidden)]
+   Error(#[cfg_attr(feature = "serde", serde(with = "error_message"))] ErrorMessage),
+}
+
+// an alias, so that serde does not deserialize the message by borrowing
+type ErrorMessage = &'static str;
+
+// The message of a Token::Error is serialized as a string.  Deserialized
+// messages are interned, so that each distinct message is leaked only once.
+#[cfg(feature = "serde")]
+mod error_message
+{
+  use std::collections::HashSet;
+  use std::sync::{Mutex,OnceLock};
+  pub fn serialize<S:serde::Serializer>(msg:&&'static str, s:S) -> std::result::Result<S::Ok,S::Error>
+  {
+    s.serialize_str(msg)
+  }
+  pub fn deserialize<'de,D:serde::Deserializer<'de>>(d:D) -> std::result::Result<&'static str,D::Error>
+  {
+    static MESSAGES:OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let msg = <String as serde::Deserialize>::deserialize(d)?;
+    let mut messages = MESSAGES.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(m) = messages.get(msg.as_str()) {return Ok(m);}
+    let m:&'static str = Box::leak(msg.into_boxed_str());
+    messages.insert(m);
+    Ok(m)
+  }
+}//mod error_message
+
+impl Token
+{
+  /// the [RawToken] with the same value, which borrows the strings of this
+  /// token: Integer becomes [RawToken::Num], Stringlit becomes
+  /// [RawToken::Strlit] and Error becomes [RawToken::LexError].  The hidden
+  /// Nothing token, which is never returned by the tokenizers, has no
+  /// equivalent.
+  pub fn as_raw(&self) -> Option<RawToken<'_>>
+  {
+    Some(match self {
+      Integer(n) => RawToken::Num(*n),
+      Float(f) => RawToken::Float(*f),
+      Symbol(s) => RawToken::Symbol(s),
+      Alphanum(s) => RawToken::Alphanum(s),
+      Keyword(s) => RawToken::Keyword(s),
+      Stringlit(s) => RawToken::Strlit(s),
+      Verbatim(s) => RawToken::Verbatim(s),
+      Newline => RawToken::Newline,
+      Nothing => {return None;},
+      Error(_) => RawToken::LexError,
+    })
+  }
+  /// the kind of the token, without its value, which is that of
+  /// [Token::as_raw]
+  pub fn kind(&self) -> Option<TokenKind> { self.as_raw().map(|t| t.kind()) }
 }
 
 fn isdelim(c:char) -> bool
@@ -114,7 +210,7 @@ fn isdelim(c:char) -> bool
 fn next_token(s:&str) -> (Token, usize) 
 {
   //let st = s.trim_start();   // assume s already trimmed
-  if s.len()<1 {return (Newline,0);}
+  if s.is_empty() {return (Newline,0);}
   let first = s.chars().next().unwrap();
   let mut index = 0;
   if first.is_alphabetic() || first=='_' {
@@ -146,7 +242,7 @@ fn next_token(s:&str) -> (Token, usize)
 ///// match token type, returns index of where token ended plus one.
 fn match_alphanum(s:&str) -> usize
 {
-   if s.len()<1 {return 0;}
+   if s.is_empty() {return 0;}
    let mut chars = s.chars();
    let mut next = chars.next().unwrap();
    if !(next.is_alphabetic() || next=='_') {return 0;}
@@ -158,7 +254,7 @@ fn match_alphanum(s:&str) -> usize
       if next.is_alphanumeric() || next=='_' { index+=1; }
       else {stop=true;}
    }
-   return index;
+   index
 }//match_alphanum
 
 fn match_num(s:&str) -> usize  // could be integer or float
@@ -183,7 +279,7 @@ fn match_num(s:&str) -> usize  // could be integer or float
 
 fn match_strlit(s:&str) -> usize
 {
-   if s.len()<1 {return 0;}
+   if s.is_empty() {return 0;}
    let mut chars = s.chars();
    let mut index = 0;
    let mut stop = false;
@@ -205,7 +301,7 @@ fn match_strlit(s:&str) -> usize
 
 fn match_symbol(s:&str) -> usize
 {
-   if s.len()<1 {return 0;}
+   if s.is_empty() {return 0;}
    let mut chars = s.chars();
    let mut c = chars.next().unwrap();
    if isdelim(c) {return 1;}
@@ -218,7 +314,7 @@ fn match_symbol(s:&str) -> usize
      if !c.is_whitespace() && !c.is_ascii_digit() && !c.is_alphanumeric() && c!='_' && c!='\"' && c!='.' && !isdelim(c)
      {index+=1;} else {stop=true;}
    }
-   return index;
+   index
 }
 
 
@@ -278,7 +374,7 @@ impl<'t> Iterator for Str_tokenizer<'t>
   type Item = Token;
   fn next(&mut self) -> Option<Token>
   {
-     if self.slice.len()<1 {None}
+     if self.slice.is_empty() {None}
      else {
         let (tok,ind) = next_token(self.slice);
         let mut return_value = None;
@@ -296,7 +392,7 @@ impl<'t> Iterator for Str_tokenizer<'t>
            */
            _ =>  { return_value = Some(tok); },
          };
-        if self.slice.len()>0 {self.slice = &self.slice[ind..].trim_start();}
+        if !self.slice.is_empty() {self.slice = self.slice[ind..].trim_start();}
         return_value
      }
   }//next
@@ -315,9 +411,9 @@ enum Mode {
   stringlit(usize), // for error reporting
 }
 fn iscomment(m:&Mode) -> bool
-{ if let Mode::comment(n) = m {true} else {false} }
+{ matches!(m, Mode::comment(_)) }
 fn isstringlit(m:&Mode) -> bool
-{ if let Mode::stringlit(n) = m {true} else {false} }
+{ matches!(m, Mode::stringlit(_)) }
 
 ///////////////////////////
 /// a [Token] [Iterator] on a given file
@@ -355,7 +451,28 @@ pub struct File_tokenizer
   begin_comment : String,
   end_comment : String,
   keep_newline : bool,
+  unicode_newlines : bool,
+  line_ending : Option<LineEnding>,
+  lookahead : VecDeque<(Token,usize,usize)>, // tokens peeked, with line and column after each
+  consumed_at : (usize,usize), // line and column after the last token consumed
 }//File_tokenizer
+/// The state of a [File_tokenizer] saved by [File_tokenizer::checkpoint].
+/// Since the tokenizer reads its file one line at a time, the state holds
+/// a copy of the current line only.
+#[derive(Clone,Debug)]
+pub struct FileCheckpoint
+{
+  offset: u64, // position in the file after the current line
+  linenum: usize,
+  column: usize,
+  line: String,
+  mode: Mode,
+  current_string: String,
+  line_ending: Option<LineEnding>,
+  lookahead: VecDeque<(Token,usize,usize)>,
+  consumed_at: (usize,usize),
+}
+
 impl File_tokenizer
 {
   /// creates a File_tokenizer given a file path, panics if file is not found
@@ -382,14 +499,23 @@ impl File_tokenizer
       current_string : String::from(""),
       begin_comment : String::from("/*"),
       end_comment : String::from("*/"),
+      unicode_newlines : false,
+      line_ending : None,
+      lookahead : VecDeque::new(),
+      consumed_at : (0,0),
     }
   }//new
 
-   /// returns the current line number being read
-   pub fn line_number(&self)->usize {self.linenum}
-   /// returns the current column (character position) on the current line
-   pub fn column_number(&self)->usize {self.column}
-   /// returns a copy of the current line being tokenized
+   /// returns the current line number being read, after the last token
+   /// consumed
+   pub fn line_number(&self)->usize
+   { if self.lookahead.is_empty() {self.linenum} else {self.consumed_at.0} }
+   /// returns the current column (character position) on the current line,
+   /// after the last token consumed
+   pub fn column_number(&self)->usize
+   { if self.lookahead.is_empty() {self.column} else {self.consumed_at.1} }
+   /// returns a copy of the current line being tokenized.  After
+   /// [Self::peek], this is the line of the last token peeked.
    pub fn current_line(&self)-> String
    {
         self.line.borrow().clone()
@@ -430,7 +556,7 @@ impl File_tokenizer
    /// default symbol is "//".  The rest of the line is skipped after
    /// this symbol.
    pub fn set_line_comment(&mut self, c:&str)
-   {if c.len()>0 {self.line_comment=String::from(c.trim());} }
+   {if !c.is_empty() {self.line_comment=String::from(c.trim());} }
    /// sets the symbols used to delineate possibly multiple-line comments.
    /// The default comment delimiters are "/\*" and "\*/".  The argument *s* should
    /// be a whitespace-separated string (e.g. "\/* */").  The function has
@@ -451,21 +577,128 @@ impl File_tokenizer
    /// other than the first line.  Newline is never emitted if inside
    /// a string literal or multi-line comment.  The default is false.
    pub fn set_keep_newline(&mut self, b:bool) {self.keep_newline=b;}   
+   /// sets option to also recognize U+2028 and U+2029 as line terminators,
+   /// in addition to "\n", "\r\n" and "\r".  The default is false.
+   pub fn set_unicode_newlines(&mut self, b:bool) {self.unicode_newlines=b;}
+   /// returns the kind of line terminator of the current line, or None if
+   /// the line is the last line and is not terminated.  The terminator is
+   /// always replaced by "\n" in the text of the line and of tokens.
+   pub fn line_ending(&self)->Option<LineEnding> {self.line_ending}
+
+   /// saves the state of the tokenizer between tokens, including its
+   /// position in the file, the current line and any string literal or
+   /// comment being read, as a [FileCheckpoint], so that it can go back to
+   /// it with [Self::restore], as needed by backtracking parsers.  Returns
+   /// an error if the position in the file cannot be determined.
+   pub fn checkpoint(&mut self) -> Result<FileCheckpoint>
+   {
+     Ok(FileCheckpoint {
+       offset: self.reader.stream_position()?,
+       lookahead: self.lookahead.clone(),
+       consumed_at: self.consumed_at,
+       linenum: self.linenum,
+       column: self.column,
+       line: self.line.borrow().clone(),
+       mode: self.mode,
+       current_string: self.current_string.clone(),
+       line_ending: self.line_ending,
+     })
+   }
+   /// returns the tokenizer to a [FileCheckpoint] saved by
+   /// [Self::checkpoint] on the same file.  Returns an error if the
+   /// file cannot be repositioned.
+   pub fn restore(&mut self, cp:&FileCheckpoint) -> Result<()>
+   {
+     self.reader.seek(SeekFrom::Start(cp.offset))?;
+     self.linenum = cp.linenum;
+     self.column = cp.column;
+     self.line.replace(cp.line.clone());
+     self.mode = cp.mode;
+     self.current_string = cp.current_string.clone();
+     self.line_ending = cp.line_ending;
+     self.lookahead = cp.lookahead.clone();
+     self.consumed_at = cp.consumed_at;
+     Ok(())
+   }
+
+   /// returns the token k positions ahead without consuming any token:
+   /// `peek(0)` is the token that will be returned next.  Returns None if
+   /// there are not that many tokens left.  [Self::line_number] and
+   /// [Self::column_number] still return the position after the last token
+   /// consumed.
+   ///
+   /// Example:
+   ///```ignore
+   ///  // a function call, as in "f(x)"
+   ///  if scanner.peek_kind(0)==Some(TokenKind::Alphanum) && scanner.peek(1)==Some(&Symbol("(".to_owned())) {
+   ///    let name = scanner.advance();
+   ///    scanner.expect_symbol("(")?;
+   ///  }
+   ///```
+   pub fn peek(&mut self, k:usize) -> Option<&Token>
+   {
+     if self.lookahead.is_empty() { self.consumed_at = (self.linenum,self.column); }
+     while self.lookahead.len()<=k {
+       match self.scan() {
+         Some(t) => { self.lookahead.push_back((t,self.linenum,self.column)); },
+         None => break,
+       }
+     }
+     self.lookahead.get(k).map(|(t,_,_)| t)
+   }
+   /// returns the kind of the token k positions ahead, see [Self::peek]
+   pub fn peek_kind(&mut self, k:usize) -> Option<TokenKind>
+   {
+     self.peek(k).and_then(|t| t.kind())
+   }
+   /// consumes and returns the next token
+   pub fn advance(&mut self) -> Option<Token>
+   {
+     match self.lookahead.pop_front() {
+       Some((t,line,column)) => { self.consumed_at=(line,column); Some(t) },
+       None => self.scan(),
+     }
+   }
+   /// consumes and returns the next token if it is of the given kind.
+   /// Otherwise, the token is not consumed, and an error message with
+   /// the line number of the token is returned.
+   pub fn expect(&mut self, kind:TokenKind) -> std::result::Result<Token,String>
+   {
+     self.expect_with(|t| t.kind()==Some(kind), &format!("{:?}",kind))
+   }
+   /// consumes and returns the next token if it is the [Symbol] sym.
+   /// Otherwise, the token is not consumed, and an error message with the
+   /// line number of the token is returned.
+   pub fn expect_symbol(&mut self, sym:&str) -> std::result::Result<Token,String>
+   {
+     self.expect_with(|t| matches!(t, Symbol(s) if s==sym), &format!("\"{}\"",sym))
+   }
+   fn expect_with<F:Fn(&Token)->bool>(&mut self, accept:F, expected:&str) -> std::result::Result<Token,String>
+   {
+     match self.peek(0) {
+       Some(t) if accept(t) => Ok(self.advance().unwrap()),
+       Some(t) => {
+         let found = format!("{:?}",t);
+         Err(format!("expected {}, found {} on line {}",expected,found,self.lookahead[0].1))
+       },
+       None => Err(format!("expected {}, found end of file",expected)),
+     }
+   }
 
    // move some match procedures here     INSIDE File_tokenizer ***
  // returns token and next index
  fn next_token(&mut self, s:&str) -> (Token, usize) 
  {
   //let st = s.trim_start();   // assume s already trimmed
-  if s.len()<1 {return (Newline,0);}
+  if s.is_empty() {return (Newline,0);}
   let first = s.chars().next().unwrap();
   let mut index = 0;
-  if (s.len()>1 && &s[0..2]==&self.begin_comment[..] && self.mode==Mode::normal) || iscomment(&self.mode) {
+  if (s.starts_with(&self.begin_comment[..]) && self.mode==Mode::normal) || iscomment(&self.mode) {
      if !iscomment(&self.mode) {self.mode = Mode::comment(self.linenum);}
      match s.find(&self.end_comment) {
        Some(index) => {
           self.mode=Mode::normal;
-          let mut ret=std::mem::replace(&mut self.current_string,String::from(""));
+          let mut ret=std::mem::take(&mut self.current_string);
           if self.keep_comments {
              ret.push_str(&s[0..index+self.end_comment.len()]);
              return (Verbatim(ret), index+self.end_comment.len());
@@ -483,7 +716,7 @@ impl File_tokenizer
      if !isstringlit(&self.mode) {self.mode = Mode::stringlit(self.linenum);}
      index = self.match_strlit(s);
      if index>0  { // found closing quote
-        let mut ret=std::mem::replace(&mut self.current_string,String::from(""));
+        let mut ret=std::mem::take(&mut self.current_string);
         ret.push_str(&s[0..index]);
         self.mode = Mode::normal;
         return (Stringlit(ret),index);
@@ -516,13 +749,13 @@ impl File_tokenizer
 
  fn match_strlit(&mut self, s:&str) -> usize
  {
-   if s.len()<1 {return 0;}
+   if s.is_empty() {return 0;}
    let mut chars = s.chars();
    let mut index = 0;
    let mut stop = false;
    let mut next = chars.next().unwrap();
    /*if next !='\"' && self.current_string.len()==0 {return 0;}
-   else*/ if next=='\"' && self.current_string.len()>0 { return 1; }
+   else*/ if next=='\"' && !self.current_string.is_empty() { return 1; }
    // above else-if is for special empty string ""
    index +=1;
    while !stop && index<s.len()
@@ -540,7 +773,7 @@ impl File_tokenizer
 
 fn match_symbol(&mut self, s:&str) -> usize
 {
-   if s.len()<1 {return 0;}
+   if s.is_empty() {return 0;}
    let mut chars = s.chars();
    let mut c = chars.next().unwrap();
    //if isdelim(c) {return 1;}
@@ -554,16 +787,68 @@ fn match_symbol(&mut self, s:&str) -> usize
      if !c.is_whitespace() && !c.is_ascii_digit() && !c.is_alphanumeric() && c!='_' && c!='\"' && c!='.' && !self.singletons.contains(&c)
      {index+=1;} else {stop=true;}
    }
-   return index;
+   index
 }
 
 }//impl File_tokenizer
 
+// reads a line terminated by "\n", "\r\n", "\r" or, if unicode is true,
+// U+2028 or U+2029, into buf with the terminator replaced by "\n".  Returns
+// the number of bytes read and the kind of terminator.
+fn read_terminated_line<R:BufRead>(reader:&mut R, buf:&mut String, unicode:bool) -> Result<(usize,Option<LineEnding>)>
+{
+  let mut line = Vec::new();
+  let mut ending = None;
+  while ending.is_none() {
+    let available = reader.fill_buf()?;
+    if available.is_empty() {break;}
+    let found = available.iter().position(|b| *b==b'\n' || *b==b'\r' || (unicode && (*b==0xA8 || *b==0xA9)));
+    let used = match found { Some(j) => j+1, None => available.len() };
+    line.extend_from_slice(&available[..used]);
+    reader.consume(used);
+    match line.last() {
+      Some(b'\n') if found.is_some() => { ending = Some(LineEnding::Lf); },
+      Some(b'\r') if found.is_some() => {
+        line.pop();
+        line.push(b'\n');
+        ending = Some(LineEnding::Cr);
+        if reader.fill_buf()?.first()==Some(&b'\n') {
+          reader.consume(1);
+          ending = Some(LineEnding::CrLf);
+        }
+      },
+      // last byte of a possible U+2028 or U+2029
+      Some(_) if found.is_some() && line.ends_with("\u{2028}".as_bytes()) => {
+        line.truncate(line.len()-3);
+        line.push(b'\n');
+        ending = Some(LineEnding::LineSeparator);
+      },
+      Some(_) if found.is_some() && line.ends_with("\u{2029}".as_bytes()) => {
+        line.truncate(line.len()-3);
+        line.push(b'\n');
+        ending = Some(LineEnding::ParagraphSeparator);
+      },
+      _ => {},
+    }
+  }//while
+  let n = line.len();
+  match String::from_utf8(line) {
+    Ok(s) => { buf.push_str(&s); Ok((n,ending)) },
+    Err(e) => Err(Error::new(std::io::ErrorKind::InvalidData,e)),
+  }
+}
+
 //////////
 impl Iterator for File_tokenizer
 {
   type Item = Token;
-  fn next(&mut self) -> Option<Token>
+  fn next(&mut self) -> Option<Token> { self.advance() }
+}//impl Iterator for File_tokenizer
+
+impl File_tokenizer
+{
+  // reads the next token from the file
+  fn scan(&mut self) -> Option<Token>
   {
      let cmlen = self.line_comment.len();
      let mut return_value = None;     
@@ -574,10 +859,11 @@ impl Iterator for File_tokenizer
        let mut brline = linerc.borrow_mut();
        if self.column >= brline.len() {
           let mut newline = String::from("");
-          match self.reader.read_line(&mut newline) {
-            Ok(n) if n>0 => {
+          match read_terminated_line(&mut self.reader,&mut newline,self.unicode_newlines) {
+            Ok((n,ending)) if n>0 => {
                //self.line.replace(newline);
                *brline = newline;
+               self.line_ending = ending;
                self.linenum +=1;
                self.column=0;
             },
@@ -618,8 +904,8 @@ impl Iterator for File_tokenizer
        }
      }// do-while loop
      return_value
-  }//next
-}//impl Iterator for File_tokenizer
+  }//scan
+}//impl File_tokenizer
 
 
 // integrate into RustLr:
@@ -0,0 +1,214 @@
+//! Command-line driver that prints the tokens of files or standard input,
+//! for debugging lexers from the shell.  Run `basic_lexer --help` for usage.
+use std::process::exit;
+use std::fmt::Write;
+use basic_lexer::*;
+
+const USAGE:&str = "\
+usage: basic_lexer [options] [file ...]
+
+Prints the tokens of each file, or of standard input if no file (or -) is
+given, with their line and column numbers.  Exits with status 1 if there
+are lexical errors, and 2 if a file cannot be read or an option is invalid.
+
+options:
+  -s, --spec FILE              read a lexer specification (.lex file)
+  --line-comment SYM           symbol that starts a line comment ('' disables)
+  --multiline-comment START END
+                               delimiters of multi-line comments ('' '' disables)
+  --operators \"OP ...\"         one and two-character operator symbols
+  --keywords \"KW ...\"          keywords
+  --keep-whitespace            print whitespace tokens
+  --keep-newline               print newline tokens
+  --keep-comments              print comment tokens
+  -f, --format text|jsonl|table
+                               output format (default text)
+  -h, --help                   print this message
+";
+
+#[derive(Clone,Copy,PartialEq)]
+enum Format { Text, Jsonl, Table }
+
+struct Options
+{
+   spec: LexSpec,
+   format: Format,
+   files: Vec<String>,
+}
+
+fn usage_error(msg:&str) -> !
+{
+  eprintln!("basic_lexer: {}\n\n{}",msg,USAGE);
+  exit(2);
+}
+
+// the argument of an option
+fn value(args:&mut std::vec::IntoIter<String>, opt:&str) -> String
+{
+  args.next().unwrap_or_else(|| usage_error(&format!("missing argument for {}",opt)))
+}
+
+fn parse_args(args:Vec<String>) -> Options
+{
+  let mut spec = LexSpec::default();
+  let mut format = Format::Text;
+  let mut files = Vec::new();
+  let mut args = args.into_iter();
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "-h" | "--help" => { print!("{}",USAGE); exit(0); },
+      "-s" | "--spec" => {
+        let path = value(&mut args,&arg);
+        // options given before --spec are kept
+        let keep = (spec.keep_whitespace,spec.keep_newline,spec.keep_comment);
+        spec = LexSpec::from_file(&path).unwrap_or_else(|e| {
+          eprintln!("basic_lexer: {}: {}",path,e);
+          exit(2);
+        });
+        spec.keep_whitespace |= keep.0; spec.keep_newline |= keep.1; spec.keep_comment |= keep.2;
+      },
+      "--line-comment" => { spec.initial_mut().line_comment = value(&mut args,&arg); },
+      "--multiline-comment" => {
+        let start = value(&mut args,&arg);
+        let end = value(&mut args,&arg);
+        if start.is_empty()!=end.is_empty() {usage_error("--multiline-comment requires both delimiters or neither");}
+        spec.initial_mut().multiline_comment = (start,end);
+      },
+      "--operators" => {
+        for op in value(&mut args,&arg).split_whitespace() {
+          let mut chars = op.chars();
+          match (chars.next(),chars.next(),chars.next()) {
+            (Some(c),None,_) => spec.initial_mut().singles.push(c),
+            (Some(_),Some(_),None) => spec.initial_mut().doubles.push(op.to_owned()),
+            _ => usage_error(&format!("operator \"{}\" is longer than two characters",op)),
+          }
+        }
+      },
+      "--keywords" => {
+        spec.initial_mut().keywords.extend(value(&mut args,&arg).split_whitespace().map(|k|k.to_owned()));
+      },
+      "--keep-whitespace" => { spec.keep_whitespace = true; },
+      "--keep-newline" => { spec.keep_newline = true; },
+      "--keep-comments" => { spec.keep_comment = true; },
+      "-f" | "--format" => {
+        format = match value(&mut args,&arg).as_str() {
+          "text" => Format::Text,
+          "jsonl" => Format::Jsonl,
+          "table" => Format::Table,
+          f => usage_error(&format!("unknown format \"{}\"",f)),
+        };
+      },
+      a if a.starts_with('-') && a!="-" => usage_error(&format!("unknown option {}",a)),
+      _ => files.push(arg),
+    }
+  }//while
+  if files.is_empty() {files.push(String::from("-"));}
+  Options{spec,format,files}
+}//parse_args
+
+// the name of the variant of a token and its value as text
+fn describe(token:&RawToken) -> (TokenKind,String)
+{
+  let value = match token {
+    RawToken::Num(n) => n.to_string(),
+    RawToken::Float(f) => f.to_string(),
+    RawToken::Char(c) => c.to_string(),
+    RawToken::Strlit(s) | RawToken::Alphanum(s) | RawToken::Keyword(s)
+      | RawToken::Symbol(s) | RawToken::Verbatim(s) | RawToken::ReaderMacro(s) => s.to_string(),
+    RawToken::Whitespace(n) => n.to_string(),
+    RawToken::WhitespaceText(s,_) => s.to_string(),
+    RawToken::RegexLit(s,f) => format!("/{}/{}",s,f),
+    RawToken::Newline | RawToken::LexError => String::new(),
+    _ => format!("{:?}",token),
+  };
+  (token.kind(),value)
+}
+
+// value of a token in JSON
+fn json_value(token:&RawToken, text:&str) -> String
+{
+  match token {
+    RawToken::Num(_) | RawToken::Whitespace(_) => text.to_owned(),
+    RawToken::Float(f) if f.is_finite() => text.to_owned(),
+    RawToken::Newline | RawToken::LexError => String::from("null"),
+    _ => json_string(text),
+  }
+}
+fn json_string(s:&str) -> String
+{
+  let mut out = String::with_capacity(s.len()+2);
+  out.push('"');
+  for c in s.chars() {
+    match c {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      '\r' => out.push_str("\\r"),
+      '\t' => out.push_str("\\t"),
+      c if (c as u32)<0x20 => { let _ = write!(out,"\\u{:04x}",c as u32); },
+      c => out.push(c),
+    }
+  }
+  out.push('"');
+  out
+}
+
+// prints the tokens of a source, returns the number of lexical errors
+fn dump(def:&LexerDef, spec:&LexSpec, source:&LexSource, format:Format) -> usize
+{
+  let mut stk = StrTokenizer::with_def(def);
+  stk.set_input(source.get_contents());
+  stk.set_source(source.get_path());
+  stk.keep_whitespace = spec.keep_whitespace;
+  stk.keep_newline = spec.keep_newline;
+  stk.keep_comment = spec.keep_comment;
+  stk.set_quiet(true);
+  let path = source.get_path();
+  let mut errors = 0;
+  let mut rows = Vec::new();
+  while let Some((token,line,column)) = stk.next_token() {
+    let (kind,text) = describe(&token);
+    match format {
+      Format::Text => println!("{}:{}:{}\t{:?}\t{:?}",path,line,column,kind,text),
+      Format::Jsonl => println!("{{\"file\":{},\"line\":{},\"column\":{},\"kind\":\"{:?}\",\"value\":{}}}",
+                                json_string(path),line,column,kind,json_value(&token,&text)),
+      Format::Table => rows.push([line.to_string(),column.to_string(),format!("{:?}",kind),format!("{:?}",text)]),
+    }
+    if let RawToken::LexError = token {
+      errors += 1;
+      if let Some(d) = stk.error_diagnostic() {d.emit(source);}
+    }
+  }//while
+  if format==Format::Table {
+    let header = ["LINE","COLUMN","KIND","VALUE"];
+    let mut widths = header.map(|h| h.len());
+    for row in &rows {
+      for (w,cell) in widths.iter_mut().zip(row) { *w = (*w).max(cell.chars().count()); }
+    }
+    println!("{}",path);
+    let print_row = |row:[&str;4]| {
+      println!("  {:>w0$}  {:>w1$}  {:<w2$}  {}",row[0],row[1],row[2],row[3],w0=widths[0],w1=widths[1],w2=widths[2]);
+    };
+    print_row(header);
+    for row in &rows { print_row([&row[0],&row[1],&row[2],&row[3]]); }
+  }
+  errors
+}//dump
+
+fn main()
+{
+  let options = parse_args(std::env::args().skip(1).collect());
+  let def = options.spec.definition();
+  let mut errors = 0;
+  for path in &options.files {
+    let source = if path=="-" {LexSource::from_stdin()} else {LexSource::new(path)};
+    match source {
+      Ok(source) => { errors += dump(&def,&options.spec,&source,options.format); },
+      Err(e) => {
+        eprintln!("basic_lexer: {}: {}",path,e);
+        exit(2);
+      },
+    }
+  }
+  if errors>0 {exit(1);}
+}//main
@@ -0,0 +1,117 @@
+//! Tokenizing many sources in parallel with a shared [LexerDef].
+use std::sync::atomic::{AtomicUsize,Ordering};
+use std::sync::Mutex;
+use crate::{RawToken,StrTokenizer,LexerDef,LexSource,Diagnostic};
+
+/// The tokens produced from one source by [BatchTokenizer], along with
+/// a [Diagnostic] for each [RawToken::LexError] among them.  The errors
+/// are not printed while tokenizing.
+#[derive(Debug)]
+pub struct TokenizedSource<'t>
+{
+   /// all tokens of the source, with their line and column numbers
+   pub tokens: Vec<(RawToken<'t>,usize,usize)>,
+   /// the tokenizer errors of the source, in the order they were found
+   pub errors: Vec<Diagnostic>,
+}
+
+/// Tokenizes lists of sources in parallel on a pool of threads, using
+/// one shared [LexerDef].  The results are always returned in the order
+/// of the inputs.
+///
+/// Example:
+///```ignore
+///  let mut def = LexerDef::new();
+///  def.add_double("==");
+///  let batch = BatchTokenizer::new(&def);
+///  let sources:Vec<LexSource> = batch.load(&["a.c","b.c"]).into_iter()
+///                                    .map(|s| s.unwrap()).collect();
+///  for (source,result) in sources.iter().zip(batch.tokenize_sources(&sources)) {
+///    println!("{}: {} tokens",source.get_path(),result.tokens.len());
+///    for error in &result.errors { error.emit(source); }
+///  }
+///```
+pub struct BatchTokenizer<'t>
+{
+   def: &'t LexerDef<'t>,
+   threads: usize,
+   /// flag that sets [StrTokenizer::keep_whitespace] for each source
+   pub keep_whitespace: bool,
+   /// flag that sets [StrTokenizer::keep_newline] for each source
+   pub keep_newline: bool,
+   /// flag that sets [StrTokenizer::keep_comment] for each source
+   pub keep_comment: bool,
+}
+impl<'t> BatchTokenizer<'t>
+{
+  /// creates a batch tokenizer with the given definition, using as many
+  /// threads as [std::thread::available_parallelism] reports.
+  pub fn new(def:&'t LexerDef<'t>) -> BatchTokenizer<'t>
+  {
+    let threads = std::thread::available_parallelism().map(|n|n.get()).unwrap_or(1);
+    BatchTokenizer { def, threads, keep_whitespace:false, keep_newline:false, keep_comment:false }
+  }
+  /// sets the number of threads used; zero is treated as one.
+  pub fn set_threads(&mut self, n:usize) { self.threads = if n==0 {1} else {n}; }
+
+  /// reads the files at the given paths in parallel, using [LexSource::new]
+  pub fn load(&self, paths:&[&'t str]) -> Vec<std::io::Result<LexSource<'t>>>
+  {
+    parallel_map(paths, self.threads, |p| LexSource::new(p))
+  }
+
+  /// tokenizes the contents of each [LexSource] in parallel
+  pub fn tokenize_sources(&self, sources:&'t [LexSource<'t>]) -> Vec<TokenizedSource<'t>>
+  {
+    parallel_map(sources, self.threads, |ls| self.tokenize_one(ls.get_contents()))
+  }
+
+  /// tokenizes each str in parallel
+  pub fn tokenize_strs(&self, inputs:&[&'t str]) -> Vec<TokenizedSource<'t>>
+  {
+    parallel_map(inputs, self.threads, |s| self.tokenize_one(s))
+  }
+
+  fn tokenize_one(&self, input:&'t str) -> TokenizedSource<'t>
+  {
+    let mut stk = self.def.tokenizer(input);
+    stk.keep_whitespace = self.keep_whitespace;
+    stk.keep_newline = self.keep_newline;
+    stk.keep_comment = self.keep_comment;
+    stk.set_quiet(true);
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+    while let Some(tok) = stk.next_token() {
+      if let RawToken::LexError = tok.0 { errors.extend(stk.error_diagnostic()); }
+      tokens.push(tok);
+    }
+    TokenizedSource { tokens, errors }
+  }
+}//impl BatchTokenizer
+
+// applies f to every item using up to the given number of scoped threads,
+// which take the next unprocessed item until none remain.  The results are
+// in the order of the items.
+fn parallel_map<'a,T,R,F>(items:&'a [T], threads:usize, f:F) -> Vec<R>
+where T:Sync, R:Send, F:Fn(&'a T)->R + Sync
+{
+  let next = AtomicUsize::new(0);
+  let results = Mutex::new(Vec::with_capacity(items.len()));
+  let workers = threads.min(items.len());
+  std::thread::scope(|scope| {
+    for _ in 0..workers {
+      scope.spawn(|| {
+        let mut done = Vec::new();
+        loop {
+          let i = next.fetch_add(1,Ordering::Relaxed);
+          if i>=items.len() {break;}
+          done.push((i,f(&items[i])));
+        }
+        results.lock().unwrap().extend(done);
+      });
+    }
+  });
+  let mut results = results.into_inner().unwrap();
+  results.sort_unstable_by_key(|(i,_)| *i);
+  results.into_iter().map(|(_,r)| r).collect()
+}
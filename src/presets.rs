@@ -0,0 +1,235 @@
+//! Predefined lexer configurations for common languages.
+use std::borrow::Cow;
+use crate::{LexerDef,StrTokenizer,NumberFormat};
+
+/// Languages for which [LexerDef::for_language] and
+/// [StrTokenizer::for_language] provide a complete configuration of
+/// symbols, comments, string literals, numbers and keywords.
+///
+/// The tokenizer recognizes symbols of at most two characters, so that
+/// longer operators such as `<<=` or `...` are returned as several
+/// symbols.  String literals are returned with their delimiters, and
+/// prefixes such as Python's `f"..."` are returned as separate
+/// [RawToken::Alphanum](crate::RawToken::Alphanum)s.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub enum Language
+{
+   /// C, with `//` and `/* */` comments and C11 keywords
+   C,
+   /// Rust, with `//` and nested `/* */` comments, underscores in numbers
+   /// and binary and octal literals
+   Rust,
+   /// Python, with `#` comments, strings in single, double or triple
+   /// quotes, and significant newlines and indentation
+   Python,
+   /// JSON, with no comments, and `true`, `false` and `null` as keywords
+   Json,
+   /// TOML, with `#` comments and strings in single, double or triple
+   /// quotes
+   Toml,
+   /// SQL, with `--` and `/* */` comments, strings in single quotes
+   /// escaped by doubling, and case-insensitive keywords
+   Sql,
+   /// Lisp and Scheme, with `;` and nested `#| |#` comments, names such
+   /// as `list->vector`, `null?` and `set-car!` as single alphanumeric
+   /// tokens, signed numbers such as `-4`, and `'`, `` ` ``, `,`, `,@`,
+   /// `#'` and `#(` as [RawToken::ReaderMacro](crate::RawToken::ReaderMacro)s
+   Lisp,
+   /// JavaScript, with `//` and `/* */` comments, strings in single,
+   /// double or back quotes, `$` in names, and regular expression literals
+   /// such as `/\d+/g`
+   JavaScript,
+}
+impl Language
+{
+  /// all the languages, in the order in which they are declared
+  pub const ALL:[Language;8] = [Language::C, Language::Rust, Language::Python,
+    Language::Json, Language::Toml, Language::Sql, Language::Lisp, Language::JavaScript];
+
+  /// the lowercase name of the language, such as "rust"
+  pub fn name(&self) -> &'static str
+  {
+    match self {
+      Language::C => "c",
+      Language::Rust => "rust",
+      Language::Python => "python",
+      Language::Json => "json",
+      Language::Toml => "toml",
+      Language::Sql => "sql",
+      Language::Lisp => "lisp",
+      Language::JavaScript => "javascript",
+    }
+  }
+  /// the language with the given name (see [Language::name]), ignoring case
+  pub fn from_name(name:&str) -> Option<Language>
+  {
+    Language::ALL.iter().copied().find(|l| l.name().eq_ignore_ascii_case(name))
+  }
+  /// the language of a file, guessed from the extension of its path
+  pub fn from_path(path:&str) -> Option<Language>
+  {
+    let ext = path.rsplit_once('.')?.1;
+    match ext.to_ascii_lowercase().as_str() {
+      "c" | "h" => Some(Language::C),
+      "rs" => Some(Language::Rust),
+      "py" | "pyi" => Some(Language::Python),
+      "json" => Some(Language::Json),
+      "toml" => Some(Language::Toml),
+      "sql" => Some(Language::Sql),
+      "lisp" | "lsp" | "cl" | "el" | "scm" | "ss" | "rkt" => Some(Language::Lisp),
+      "js" | "mjs" | "cjs" => Some(Language::JavaScript),
+      _ => None,
+    }
+  }
+  /// whether newlines and whitespaces are significant in the language,
+  /// in which case [StrTokenizer::for_language] sets the tokenizer's
+  /// keep_newline and keep_whitespace flags
+  pub fn significant_layout(&self) -> bool { *self==Language::Python }
+}//impl Language
+
+const C_KEYWORDS:&str = "auto break case char const continue default do double else enum
+  extern float for goto if inline int long register restrict return short signed sizeof
+  static struct switch typedef union unsigned void volatile while _Alignas _Alignof
+  _Atomic _Bool _Complex _Generic _Imaginary _Noreturn _Static_assert _Thread_local";
+const RUST_KEYWORDS:&str = "as async await break const continue crate dyn else enum extern
+  false fn for if impl in let loop match mod move mut pub ref return self Self static
+  struct super trait true type union unsafe use where while";
+const PYTHON_KEYWORDS:&str = "False None True and as assert async await break class continue
+  def del elif else except finally for from global if import in is lambda nonlocal not or
+  pass raise return try while with yield";
+const SQL_KEYWORDS:&str = "add all alter and any as asc between by case check column
+  constraint create cross default delete desc distinct drop else end exists foreign from
+  full group having in index inner insert into is join key left like limit not null on or
+  order outer primary references right select set table then union unique update values
+  view when where with";
+const LISP_KEYWORDS:&str = "define define-syntax defun defmacro defvar lambda let let* letrec
+  if cond case and or quote quasiquote unquote begin progn set! setq do loop when unless";
+const JS_KEYWORDS:&str = "async await break case catch class const continue debugger default
+  delete do else export extends false finally for function if import in instanceof let new
+  null of return static super switch this throw true try typeof var void while with yield";
+// characters of lisp symbols other than letters, digits and '_'
+const LISP_IDENT_START:&str = "!$%&*/:<=>?^~+-";
+const LISP_IDENT_CONTINUE:&str = "!$%&*/:<=>?^~+-.@";
+
+impl<'t> LexerDef<'t>
+{
+  /// creates a definition for the given [Language].  Further symbols and
+  /// keywords can be added to it.
+  ///
+  /// Example:
+  ///```ignore
+  ///  let def = LexerDef::for_language(Language::Rust);
+  ///  let tokens:Vec<_> = def.tokenizer("let x = 0b1010_1010;").collect();
+  ///```
+  pub fn for_language(lang:Language) -> LexerDef<'t>
+  {
+    let mut def = LexerDef::new();
+    let all_numbers = NumberFormat{hex:true, binary:true, octal:true, exponents:true, underscores:true};
+    match lang {
+      Language::C => {
+        for c in ";,.?:~+-*/%<>=!&|^#".chars() {def.add_single(c);}
+        for d in ["==","!=","<=",">=","&&","||","++","--","->","<<",">>",
+                  "+=","-=","*=","/=","%=","&=","|=","^="] {def.add_double(d);}
+        def.add_keywords(C_KEYWORDS);
+        def.set_number_format(NumberFormat{exponents:true, ..NumberFormat::default()});
+      },
+      Language::Rust => {
+        for c in ";,.?:~+-*/%<>=!&|^#$@".chars() {def.add_single(c);}
+        for d in ["==","!=","<=",">=","&&","||","->","=>","::","..","<<",">>",
+                  "+=","-=","*=","/=","%=","&=","|=","^="] {def.add_double(d);}
+        def.add_keywords(RUST_KEYWORDS);
+        def.set_nested_comments(true);
+        def.set_number_format(all_numbers);
+      },
+      Language::Python => {
+        def.set_line_comment("#");
+        def.set_multiline_comments("");
+        for c in ";,.:~+-*/%<>=!&|^@".chars() {def.add_single(c);}
+        for d in ["==","!=","<=",">=","**","//","->",":=","<<",">>",
+                  "+=","-=","*=","/=","%=","&=","|=","^=","@="] {def.add_double(d);}
+        def.add_keywords(PYTHON_KEYWORDS);
+        def.set_string_delimiters("\"'");
+        def.set_triple_quotes(true);
+        def.set_char_literals(false);
+        def.set_number_format(all_numbers);
+      },
+      Language::Json => {
+        def.set_line_comment("");
+        def.set_multiline_comments("");
+        for c in ":,-".chars() {def.add_single(c);}
+        def.add_keywords("true false null");
+        def.set_number_format(NumberFormat{hex:false, exponents:true, ..NumberFormat::default()});
+      },
+      Language::Toml => {
+        def.set_line_comment("#");
+        def.set_multiline_comments("");
+        for c in "=,.:+-".chars() {def.add_single(c);}
+        def.add_keywords("true false inf nan");
+        def.set_string_delimiters("\"'");
+        def.set_triple_quotes(true);
+        def.set_char_literals(false);
+        def.set_number_format(all_numbers);
+      },
+      Language::Sql => {
+        def.set_line_comment("--");
+        for c in ";,.*+-/%=<>|:".chars() {def.add_single(c);}
+        for d in ["<>","!=","<=",">=","||","::"] {def.add_double(d);}
+        def.add_keywords(SQL_KEYWORDS);
+        def.set_case_insensitive_keywords(true);
+        def.set_string_delimiters("'\"");
+        def.set_backslash_escapes(false);
+        def.set_char_literals(false);
+        def.set_number_format(NumberFormat{hex:false, exponents:true, ..NumberFormat::default()});
+      },
+      Language::Lisp => {
+        def.set_line_comment(";");
+        def.set_multiline_comment_delimiters("#|","|#");
+        def.set_nested_comments(true);
+        def.set_identifier_chars(LISP_IDENT_START,LISP_IDENT_CONTINUE);
+        def.add_single('#');
+        def.add_reader_macros("' ` , ,@ #' #(");
+        def.add_keywords(LISP_KEYWORDS);
+        def.set_char_literals(false);
+        def.set_number_format(NumberFormat{hex:false, exponents:true, ..NumberFormat::default()});
+      },
+      Language::JavaScript => {
+        for c in ";,.?:~+-*/%<>=!&|^".chars() {def.add_single(c);}
+        for d in ["==","!=","<=",">=","&&","||","??","?.","++","--","=>","**","<<",">>",
+                  "+=","-=","*=","/=","%=","&=","|=","^="] {def.add_double(d);}
+        def.add_keywords(JS_KEYWORDS);
+        def.set_identifier_chars("$","$");
+        def.set_string_delimiters("\"'`");
+        def.set_char_literals(false);
+        def.set_regex_literals(true);
+        def.set_number_format(all_numbers);
+      },
+    }//match lang
+    def
+  }//for_language
+}
+
+impl<'t> StrTokenizer<'t>
+{
+  /// creates a tokenizer configured for the given [Language], *does not*
+  /// set input.  For languages with [Language::significant_layout], the
+  /// keep_newline and keep_whitespace flags are set.
+  ///
+  /// Example:
+  ///```ignore
+  ///  let source = LexSource::new("Cargo.toml").unwrap();
+  ///  let mut tokenizer = StrTokenizer::for_language(Language::Toml);
+  ///  tokenizer.set_input(source.get_contents());
+  ///  while let Some((token,line,column)) = tokenizer.next() {
+  ///    println!("{:?}, line {}, column {}",token,line,column);
+  ///  }
+  ///```
+  pub fn for_language(lang:Language) -> StrTokenizer<'t>
+  {
+    let mut stk = StrTokenizer::with_cow(Cow::Owned(LexerDef::for_language(lang)));
+    if lang.significant_layout() {
+      stk.keep_newline = true;
+      stk.keep_whitespace = true;
+    }
+    stk
+  }
+}
@@ -0,0 +1,80 @@
+//! Integration with the *[rustlr](https://docs.rs/rustlr/latest/rustlr/)*
+//! parser generator, enabled with the `rustlr` feature.
+use crate::{RawToken,StrTokenizer,LexSource};
+use crate::zero_copy::find_line_break;
+use rustlr::{Tokenizer,TerminalToken};
+
+/// Adapter that allows a [StrTokenizer] to drive a rustlr parser by
+/// implementing rustlr's [Tokenizer] trait.  Since the names of terminal
+/// symbols and the type of their semantic values depend on the grammar,
+/// each [RawToken] is converted by a translation function supplied
+/// by the user.  The translation function returns the name of the
+/// terminal symbol and its value, or None if the token should be skipped
+/// (such as comments or whitespaces).  Line and column information are
+/// transferred from the tokenizer automatically.
+///
+/// Example, for a grammar with terminals `num`, `ID` and symbols:
+///```ignore
+///  let source = LexSource::new("input.txt").unwrap();
+///  let mut lexer = RustlrTokenizer::from_source(&source, |tok| match tok {
+///     RawToken::Num(n) => Some(("num",n)),
+///     RawToken::Alphanum(_) => Some(("ID",0)),
+///     RawToken::Symbol(s) => Some((s,0)),
+///     _ => None,
+///  });
+///  lexer.tokenizer.add_double("==");
+///  let result = parser.parse(&mut lexer);
+///```
+pub struct RustlrTokenizer<'t,AT:Default>
+{
+   /// the underlying tokenizer, which can be configured directly
+   pub tokenizer: StrTokenizer<'t>,
+   translate: Box<dyn FnMut(RawToken<'t>)->Option<(&'t str,AT)> + 't>,
+}
+impl<'t,AT:Default> RustlrTokenizer<'t,AT>
+{
+  /// creates an adapter from a tokenizer and a translation function
+  pub fn new<F>(tokenizer:StrTokenizer<'t>, translate:F) -> RustlrTokenizer<'t,AT>
+  where F: FnMut(RawToken<'t>)->Option<(&'t str,AT)> + 't
+  {
+    RustlrTokenizer { tokenizer, translate:Box::new(translate) }
+  }
+  /// creates an adapter that tokenizes the contents of a [LexSource]
+  pub fn from_source<F>(ls:&'t LexSource<'t>, translate:F) -> RustlrTokenizer<'t,AT>
+  where F: FnMut(RawToken<'t>)->Option<(&'t str,AT)> + 't
+  {
+    RustlrTokenizer::new(StrTokenizer::from_source(ls),translate)
+  }
+}//impl RustlrTokenizer
+
+impl<'t,AT:Default> Tokenizer<'t,AT> for RustlrTokenizer<'t,AT>
+{
+  fn nextsym(&mut self) -> Option<TerminalToken<'t,AT>>
+  {
+    while let Some((tok,line,column)) = self.tokenizer.next_token() {
+      if let Some((sym,value)) = (self.translate)(tok) {
+        return Some(TerminalToken::new(sym,value,line,column));
+      }
+    }
+    None
+  }
+  fn linenum(&self) -> usize {self.tokenizer.line()}
+  fn column(&self) -> usize {self.tokenizer.column()}
+  fn position(&self) -> usize {self.tokenizer.position()}
+  fn current_line(&self) -> &str {self.tokenizer.current_line()}
+  fn get_line(&self, i:usize) -> Option<&str>
+  {
+    if i<1 {return None;}
+    // lines end as they do for the tokenizer, including lone '\r's
+    let (input,unicode) = (self.tokenizer.get_input(),self.tokenizer.unicode_newlines);
+    let mut start = 0;
+    for _ in 1..i {
+      let (pos,_,n) = find_line_break(input,start,unicode)?;
+      start = pos+n;
+    }
+    let end = find_line_break(input,start,unicode).map(|(pos,_,_)|pos).unwrap_or(input.len());
+    Some(&input[start..end])
+  }
+  fn get_slice(&self, start:usize, end:usize) -> &str {self.tokenizer.get_slice(start,end)}
+  fn source(&self) -> &str {self.tokenizer.get_source()}
+}//impl Tokenizer for RustlrTokenizer
@@ -0,0 +1,139 @@
+//! Mapping global byte offsets across many sources.
+use crate::{StrTokenizer,LexSource};
+
+/// A range of global byte offsets assigned by a [SourceMap], from `start`
+/// (inclusive) to `end` (exclusive).  Spans from different sources of the
+/// same map never overlap, so they can be compared and ordered directly.
+#[cfg_attr(feature = "serde", derive(serde::Serialize,serde::Deserialize))]
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash,PartialOrd,Ord,Default)]
+pub struct Span
+{
+   /// global offset of the first byte
+   pub start: usize,
+   /// global offset after the last byte
+   pub end: usize,
+}
+impl Span
+{
+  /// creates a span from the given global offsets
+  pub fn new(start:usize, end:usize) -> Span { Span{start,end} }
+  /// the number of bytes in the span
+  pub fn len(&self) -> usize { self.end.saturating_sub(self.start) }
+  /// whether the span is empty
+  pub fn is_empty(&self) -> bool { self.end<=self.start }
+  /// the smallest span that contains both spans
+  pub fn to(&self, other:Span) -> Span
+  {
+    Span{ start:self.start.min(other.start), end:self.end.max(other.end) }
+  }
+}
+
+struct MappedSource<'t>
+{
+   source: LexSource<'t>,
+   base: usize,
+}
+
+/// Registers many [LexSource]s and assigns each a distinct range of global
+/// byte offsets, so that a position in any of the sources can be stored as
+/// one number, and resolved back to the path, line and column of the source.
+/// The id of each source is set to its index in the map, and passed on to
+/// the tokenizers created by [SourceMap::tokenizer].
+///
+/// Example:
+///```ignore
+///  let mut map = SourceMap::new();
+///  let a = map.add(LexSource::new("a.c").unwrap());
+///  let b = map.add(LexSource::from_string("<prelude>", prelude_text));
+///  let mut tokenizer = map.tokenizer(b);
+///  while let Some((token,line,column)) = tokenizer.next() {
+///    let offset = map.global_offset(b, tokenizer.position());
+///    let (path,line,column) = map.resolve(offset).unwrap();
+///  }
+///```
+pub struct SourceMap<'t>
+{
+   sources: Vec<MappedSource<'t>>,
+   next_base: usize,
+}
+impl<'t> Default for SourceMap<'t>
+{
+  fn default() -> Self { SourceMap::new() }
+}
+impl<'t> SourceMap<'t>
+{
+  /// creates an empty source map
+  pub fn new() -> SourceMap<'t> { SourceMap{ sources:Vec::new(), next_base:0 } }
+
+  /// adds a source to the map and returns its id.  The source is assigned
+  /// the global offsets following those of the previous source, with a gap
+  /// of one so that the end offset of a source belongs to no other source.
+  pub fn add(&mut self, mut source:LexSource<'t>) -> usize
+  {
+    let id = self.sources.len();
+    source.set_id(id);
+    let base = self.next_base;
+    self.next_base += source.get_contents().len()+1;
+    self.sources.push(MappedSource{source,base});
+    id
+  }
+  /// the number of sources in the map
+  pub fn len(&self) -> usize { self.sources.len() }
+  /// whether the map has no sources
+  pub fn is_empty(&self) -> bool { self.sources.is_empty() }
+  /// returns the source with the given id
+  pub fn get(&self, id:usize) -> Option<&LexSource<'t>> { self.sources.get(id).map(|m| &m.source) }
+  /// returns the global offset of the first byte of the source with the
+  /// given id
+  pub fn base(&self, id:usize) -> Option<usize> { self.sources.get(id).map(|m| m.base) }
+  /// converts a byte position in the source with the given id, such as
+  /// [StrTokenizer::position], into a global offset.  Panics if there is
+  /// no such source.
+  pub fn global_offset(&self, id:usize, position:usize) -> usize { self.sources[id].base+position }
+  /// converts a range of byte positions in the source with the given id
+  /// into a global [Span].  Panics if there is no such source.
+  pub fn span(&self, id:usize, start:usize, end:usize) -> Span
+  {
+    let base = self.sources[id].base;
+    Span{ start:base+start, end:base+end }
+  }
+  /// returns the id of the source that contains the given global offset,
+  /// including the offset just past its end.
+  pub fn lookup(&self, offset:usize) -> Option<usize>
+  {
+    let i = self.sources.partition_point(|m| m.base<=offset);
+    if i==0 {return None;}
+    let m = &self.sources[i-1];
+    if offset<=m.base+m.source.get_contents().len() {Some(i-1)} else {None}
+  }
+  /// converts a global offset into the id of its source and the position
+  /// within that source
+  pub fn local(&self, offset:usize) -> Option<(usize,usize)>
+  {
+    self.lookup(offset).map(|id| (id, offset-self.sources[id].base))
+  }
+  /// resolves a global offset into the path of its source and the line and
+  /// column numbers (starting at 1) of the offset, counted in the same way
+  /// as by [StrTokenizer], using [LexSource::offset_to_line_col].
+  pub fn resolve(&self, offset:usize) -> Option<(&str,usize,usize)>
+  {
+    let (id,position) = self.local(offset)?;
+    let source = &self.sources[id].source;
+    let (line,column) = source.offset_to_line_col(position)?;
+    Some((source.get_path(), line, column))
+  }
+  /// returns the text covered by a span, or None if the span does not lie
+  /// within a single source or is not on character boundaries
+  pub fn span_text(&self, span:Span) -> Option<&str>
+  {
+    let (id,start) = self.local(span.start)?;
+    let end = span.end.checked_sub(self.sources[id].base)?;
+    self.sources[id].source.get_contents().get(start..end)
+  }
+  /// creates a tokenizer for the source with the given id, using
+  /// [StrTokenizer::from_source].  Panics if there is no such source.
+  pub fn tokenizer(&self, id:usize) -> StrTokenizer<'_>
+  {
+    StrTokenizer::from_source(&self.sources[id].source)
+  }
+}//impl SourceMap
@@ -0,0 +1,253 @@
+//! Tokenizing a stream without reading it entirely into memory.
+use std::io::{self,BufRead,BufReader,Read};
+use std::fs::File;
+use crate::{RawToken,OwnedToken,StrTokenizer,LexerDef,TokenKind};
+
+/// Default capacity of the buffer of a [StreamTokenizer], in bytes
+pub const DEFAULT_STREAM_BUFFER:usize = 64*1024;
+
+// A token that ends fewer than this many bytes before the end of the buffer
+// is scanned again after more input is read, because the bytes that follow
+// it can change how it is recognized: the digits after "3." in a float, the
+// second character of a double, or the closing quote of a char literal such
+// as '\n', which is at most 5 bytes after the opening quote.  The margin is
+// larger for definitions with longer comment delimiters, hook prefixes or
+// reader macros, of which a token may be the start.
+const REFILL_MARGIN:usize = 8;
+
+/// Tokenizer that reads from any [BufRead] through a bounded sliding buffer,
+/// so that inputs much larger than memory can be tokenized.  It recognizes
+/// the same tokens as [StrTokenizer], with the rules of a [LexerDef], and
+/// produces [OwnedToken]s with line and column numbers.
+///
+/// Tokens that span the end of the buffer, such as multi-line strings and
+/// comments, are re-scanned after more input is read, and so are tokens
+/// that end close to the end of the buffer.  A token that is longer than
+/// the capacity of the buffer cannot be recognized as a whole.  A line
+/// comment that long is reported as a [OwnedToken::LexError] and skipped
+/// up to the end of its line.  A string or multi-line comment that long is
+/// also reported as a LexError, but its part that does not fit in the
+/// buffer is then scanned as code.  Other tokens are split.
+///
+/// Example:
+///```ignore
+///  let def = LexerDef::new();
+///  let mut tokenizer = StreamTokenizer::from_file(&def, "huge.log").unwrap();
+///  while let Some((token,line,column)) = tokenizer.next() {
+///    println!("{:?} at line {}, column {}",token,line,column);
+///  }
+///  if let Some(err) = tokenizer.take_io_error() { eprintln!("{}",err); }
+///```
+pub struct StreamTokenizer<'d,R:BufRead>
+{
+   def: &'d LexerDef<'d>,
+   reader: R,
+   buf: String,
+   pending: Vec<u8>, // bytes of incomplete utf8 characters
+   start: usize,     // position in buf of the next token
+   offset: usize,    // absolute position of buf[0] in the stream
+   capacity: usize,
+   margin: usize, // see REFILL_MARGIN
+   eof: bool,
+   line: usize,
+   line_start: usize, // absolute position where the current line starts
+   io_error: Option<io::Error>,
+   utf8_error: Option<io::Error>, // invalid input after the end of buf
+   previous: Option<(TokenKind,String)>, // last token that is not trivia
+   quiet: bool,
+   /// flag that has the same meaning as [StrTokenizer::keep_whitespace]
+   pub keep_whitespace: bool,
+   /// flag that has the same meaning as [StrTokenizer::keep_newline]
+   pub keep_newline: bool,
+   /// flag that has the same meaning as [StrTokenizer::keep_comment]
+   pub keep_comment: bool,
+}
+impl<'d,R:BufRead> StreamTokenizer<'d,R>
+{
+  /// creates a stream tokenizer with a buffer of [DEFAULT_STREAM_BUFFER] bytes
+  pub fn new(def:&'d LexerDef<'d>, reader:R) -> StreamTokenizer<'d,R>
+  {
+    StreamTokenizer::with_capacity(def,reader,DEFAULT_STREAM_BUFFER)
+  }
+  /// creates a stream tokenizer with a buffer of the given number of bytes
+  pub fn with_capacity(def:&'d LexerDef<'d>, reader:R, capacity:usize) -> StreamTokenizer<'d,R>
+  {
+    StreamTokenizer {
+      def, reader,
+      buf: String::with_capacity(capacity),
+      pending: Vec::new(),
+      start:0, offset:0, capacity:capacity.max(16), eof:false,
+      margin: REFILL_MARGIN.max(def.longest_prefix()+1),
+      line:1, line_start:0, io_error:None, utf8_error:None, previous:None, quiet:false,
+      keep_whitespace:false, keep_newline:false, keep_comment:false,
+    }
+  }
+  /// the current line number of the tokenizer
+  pub fn line(&self) -> usize {self.line}
+  /// the current column of the tokenizer
+  pub fn column(&self) -> usize {self.position()-self.line_start+1}
+  /// the current absolute byte position of the tokenizer in the stream
+  pub fn position(&self) -> usize {self.offset+self.start}
+  /// returns the I/O error that stopped the tokenizer, if any.  An I/O
+  /// error is returned as a [OwnedToken::LexError] and ends the stream of
+  /// tokens.  So does input that is not valid UTF-8, after the tokens
+  /// before it: the LexError is at the first invalid byte.
+  pub fn take_io_error(&mut self) -> Option<io::Error> {self.io_error.take()}
+  /// suppresses the printing of errors to stderr, like
+  /// [StrTokenizer::set_quiet].  The default is false.
+  pub fn set_quiet(&mut self, q:bool) {self.quiet=q;}
+
+  // discards the consumed part of the buffer and reads until the buffer
+  // is full or the end of the stream is reached.  Input that is not valid
+  // UTF-8 ends the stream like the end of input, and its error is kept to
+  // be reported after the tokens before it.
+  fn fill(&mut self) -> io::Result<()>
+  {
+    if self.start>0 {
+      self.buf.drain(..self.start);
+      self.offset += self.start;
+      self.start = 0;
+    }
+    while !self.eof && self.buf.len()<self.capacity
+    {
+      let want = (self.capacity-self.buf.len()) as u64;
+      let n = (&mut self.reader).take(want).read_until(b'\n',&mut self.pending)?;
+      if n==0 {
+        self.eof = true;
+        if !self.pending.is_empty() {
+          self.utf8_error = Some(io::Error::new(io::ErrorKind::InvalidData,"stream did not end with valid UTF-8"));
+        }
+        break;
+      }
+      match std::str::from_utf8(&self.pending) {
+        Ok(s) => { self.buf.push_str(s); self.pending.clear(); },
+        Err(e) if e.error_len().is_none() => { // incomplete character at end
+          let valid = e.valid_up_to();
+          self.buf.push_str(std::str::from_utf8(&self.pending[..valid]).unwrap());
+          self.pending.drain(..valid);
+        },
+        Err(e) => {
+          let valid = e.valid_up_to();
+          self.buf.push_str(std::str::from_utf8(&self.pending[..valid]).unwrap());
+          self.eof = true;
+          self.utf8_error = Some(io::Error::new(io::ErrorKind::InvalidData,e));
+        },
+      }
+    }//while
+    Ok(())
+  }//fill
+
+  /// returns the next token along with its starting line and column numbers,
+  /// or None at the end of the stream.
+  pub fn next_token(&mut self) -> Option<(OwnedToken,usize,usize)>
+  {
+    if self.io_error.is_some() {return None;}
+    loop {
+      let window = &self.buf[self.start..];
+      let mut stk = StrTokenizer::with_def(self.def);
+      stk.set_quiet(true);
+      stk.keep_whitespace = self.keep_whitespace;
+      stk.keep_newline = self.keep_newline;
+      // comments are always returned, so that a comment that spans the end
+      // of the buffer is scanned again
+      stk.keep_comment = true;
+      // trailing whitespaces are trimmed only at the end of the stream, and
+      // are skipped before invalid input to find its position
+      if self.eof && self.utf8_error.is_none() {stk.set_input(window);}
+      else {stk.set_untrimmed_input(window);}
+      stk.set_preceding(self.previous.as_ref().map(|(k,s)| (*k,s.as_str())));
+      let result = stk.next_token();
+      let consumed = stk.position();
+      let near_end = consumed+self.margin>window.len();
+      let full = self.start==0 && self.buf.len()>=self.capacity;
+      if !self.eof && (result.is_none() || near_end) && !full {
+        // the token may continue past the buffer: read more and try again
+        if let Err(e) = self.fill() {
+          self.io_error = Some(e);
+          return Some((OwnedToken::LexError,self.line,self.column()));
+        }
+        continue;
+      }
+      let (tok,ln,col) = match result {
+        Some(r) => r,
+        None if self.eof && self.utf8_error.is_none() => {return None;},
+        None => { // full buffer of skipped whitespaces, or invalid input
+          if stk.line()>1 { self.line_start = self.offset+self.start+stk.line_start(); }
+          self.line += stk.line()-1;
+          self.start += consumed;
+          if let Some(e) = self.utf8_error.take() {
+            if !self.quiet {
+              eprintln!("Tokenizer error: {} on line {}, column {}",e,self.line,self.column());
+            }
+            self.io_error = Some(e);
+            return Some((OwnedToken::LexError,self.line,self.column()));
+          }
+          if let Err(e) = self.fill() {
+            self.io_error = Some(e);
+            return Some((OwnedToken::LexError,self.line,self.column()));
+          }
+          continue;
+        },
+      };
+      let tokstart = self.offset+self.start;
+      let column = if ln==1 {tokstart+col-self.line_start} else {col};
+      let line = self.line+ln-1;
+      // a line comment that fills the buffer is longer than it
+      let overlong = full && !self.eof && stk.is_comment() && consumed==window.len();
+      let error = match tok {
+        _ if overlong => Some("comment longer than stream buffer"),
+        RawToken::LexError if full && !self.eof => Some("token longer than stream buffer"),
+        RawToken::LexError => Some(stk.last_error()),
+        _ => None,
+      };
+      let tok = match error {
+        Some(msg) => {
+          if !self.quiet {
+            eprintln!("Tokenizer error: {} starting on line {}, column {}",msg,line,column);
+          }
+          RawToken::LexError
+        },
+        None => tok,
+      };
+      if stk.line()>1 { self.line_start = tokstart+stk.line_start(); }
+      self.line += stk.line()-1;
+      if !self.keep_comment && stk.is_comment() && !overlong { self.start += consumed; continue; }
+      let previous = if tok.is_trivia() {None} else {Some(stk.previous().map(|(k,s)| (k,s.to_owned())))};
+      let token = tok.into_owned();
+      if let Some(p) = previous {self.previous = p;}
+      self.start += consumed;
+      if overlong {self.skip_line();}
+      return Some((token,line,column));
+    }//loop
+  }//next_token
+
+  // discards the rest of a line comment that is longer than the buffer, up
+  // to the line terminator that ends it
+  fn skip_line(&mut self)
+  {
+    loop {
+      if let Some(i) = self.buf[self.start..].find(['\n','\r']) {
+        self.start += i;
+        return;
+      }
+      self.start = self.buf.len();
+      if self.eof {return;}
+      if let Err(e) = self.fill() { self.io_error = Some(e); return; }
+    }
+  }//skip_line
+}//impl StreamTokenizer
+
+impl<'d> StreamTokenizer<'d,BufReader<File>>
+{
+  /// creates a stream tokenizer that reads from the file at the given path
+  pub fn from_file(def:&'d LexerDef<'d>, path:&str) -> io::Result<StreamTokenizer<'d,BufReader<File>>>
+  {
+    Ok(StreamTokenizer::new(def,BufReader::new(File::open(path)?)))
+  }
+}
+
+impl<'d,R:BufRead> Iterator for StreamTokenizer<'d,R>
+{
+  type Item = (OwnedToken,usize,usize);
+  fn next(&mut self) -> Option<(OwnedToken,usize,usize)> { self.next_token() }
+}
@@ -0,0 +1,395 @@
+//! Table-driven lexers that run from static tables, as generated from
+//! lexer specifications by [LexSpec::generate](crate::LexSpec::generate)
+//! and by the [lexer!](crate::lexer!) macro.
+
+use std::borrow::Cow;
+use crate::RawToken::{self,*};
+use crate::{NumberFormat,TokenKind};
+use crate::zero_copy::{line_break_at,scan_number};
+
+/// A set of characters, given by a string of characters and ranges such
+/// as "a-zA-Z_".  A '-' stands for itself at the start or the end of the
+/// string, and '\\' escapes the next character.  Sets can be created in
+/// constant expressions; ascii characters are looked up in a bit set, and
+/// other characters by scanning the string.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub struct CharSet<'a>
+{
+   ascii: u128,
+   ranges: &'a str,
+}
+impl<'a> CharSet<'a>
+{
+  /// creates the set of characters described by the given string
+  pub const fn new(ranges:&'a str) -> CharSet<'a>
+  {
+    let b = ranges.as_bytes();
+    let mut ascii = 0u128;
+    let mut i = 0;
+    while i<b.len() {
+      if b[i]==b'\\' && i+1<b.len() {i+=1;}
+      let lo = b[i];
+      let mut hi = lo;
+      if i+2<b.len() && b[i+1]==b'-' {
+        i+=2;
+        if b[i]==b'\\' && i+1<b.len() {i+=1;}
+        hi = b[i];
+      }
+      // ranges that end outside of ascii are cut at the end of ascii, the
+      // rest is found by contains
+      if lo<128 {
+        let hi = if hi<128 {hi} else {127};
+        let mut c = lo;
+        while c<=hi { ascii |= 1u128<<c; c+=1; }
+      }
+      i+=1;
+    }
+    CharSet{ascii,ranges}
+  }
+  // whether the set contains the ascii character b, in constant expressions
+  pub(crate) const fn contains_ascii(&self, b:u8) -> bool
+  {
+    b<128 && self.ascii & (1u128<<b) != 0
+  }
+  /// the string that describes the set
+  pub fn as_str(&self) -> &'a str {self.ranges}
+  /// whether the set contains the character
+  pub fn contains(&self, c:char) -> bool
+  {
+    if c.is_ascii() {return self.ascii & (1u128<<(c as u32)) != 0;}
+    let mut cs = self.ranges.chars().peekable();
+    let unescape = |cs:&mut std::iter::Peekable<std::str::Chars>, c:char| if c=='\\' {cs.next().unwrap_or(c)} else {c};
+    while let Some(lo) = cs.next() {
+      let lo = unescape(&mut cs,lo);
+      let mut hi = lo;
+      if cs.peek()==Some(&'-') {
+        let mut ahead = cs.clone();
+        ahead.next();
+        if let Some(h) = ahead.next() { hi = unescape(&mut ahead,h); cs = ahead; }
+      }
+      if lo<=c && c<=hi {return true;}
+    }
+    false
+  }
+}//impl CharSet
+
+/// How delimiters are escaped inside the string literals of a [StringRule]
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub enum Escape
+{
+  /// a backslash escapes the next character, as in C
+  Backslash,
+  /// the end delimiter is escaped by doubling it, as in SQL ('it''s')
+  Doubled,
+  /// nothing is escaped, as in Rust's raw strings
+  Raw,
+}
+
+/// A kind of string literal, from its start delimiter to its end
+/// delimiter.  String literals are returned as [RawToken::Strlit]s that
+/// include their delimiters, and can span lines.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub struct StringRule<'a>
+{
+   /// the delimiter that starts the literal
+   pub start: &'a str,
+   /// the delimiter that ends the literal
+   pub end: &'a str,
+   /// how the end delimiter is escaped
+   pub escape: Escape,
+}
+
+/// A class of tokens made of a character of the `start` set followed by
+/// any number of characters of the `cont` set.  The kind of the class is
+/// [TokenKind::Alphanum] (which gives a [RawToken::Keyword] if the text is a
+/// keyword), [TokenKind::Symbol] or [TokenKind::Verbatim]; other kinds are
+/// treated as Verbatim.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub struct TokenClass<'a>
+{
+   /// the kind of token returned
+   pub kind: TokenKind,
+   /// the characters that can start a token
+   pub start: CharSet<'a>,
+   /// the characters that can continue a token
+   pub cont: CharSet<'a>,
+}
+
+/// A symbol that switches the lexer to another state, after it is
+/// returned as a [RawToken::Symbol]
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub struct Transition<'a>
+{
+   /// the symbol, which is recognized in its state even if it is not a
+   /// single or double symbol
+   pub on: &'a str,
+   /// the index of the state to switch to
+   pub to: usize,
+}
+
+/// The token classes used by a state that has none of its own: the
+/// alphanumeric sequences and the sequences of other symbols recognized by
+/// [StrTokenizer](crate::StrTokenizer)
+pub static DEFAULT_CLASSES: [TokenClass<'static>;2] = [
+  TokenClass{kind:TokenKind::Alphanum, start:CharSet::new("a-zA-Z_"), cont:CharSet::new("a-zA-Z0-9_")},
+  TokenClass{kind:TokenKind::Symbol, start:CharSet::new(r"!@#$%^&*?+/.,<>=~`';:|\\-"), cont:CharSet::new(r"!@#$%^&*?+/.,<>=~`';:|\\-")},
+];
+
+/// The rules of one state of a [TableLexer].  In each position, the lexer
+/// of the state skips whitespaces and then tries, in order: the symbols of
+/// its transitions, comments, string literals, double and single symbols,
+/// numbers in the given format and the token classes.  Characters that
+/// start none of these are errors.  A state with `text` set instead returns
+/// everything up to the next symbol of its transitions, whitespaces included,
+/// as a [RawToken::Verbatim], as in the body of a template string.
+#[derive(Clone,Debug,PartialEq)]
+pub struct StateTable<'a>
+{
+   /// name of the state
+   pub name: &'a str,
+   /// single-character symbols
+   pub singles: Cow<'a,[char]>,
+   /// two-character symbols
+   pub doubles: Cow<'a,[&'a str]>,
+   /// keywords, returned as [RawToken::Keyword]s by alphanumeric classes
+   pub keywords: Cow<'a,[&'a str]>,
+   /// symbol that starts a single-line comment, empty if disabled
+   pub line_comment: &'a str,
+   /// start and end delimiters of multi-line comments, empty if disabled
+   pub multiline_comment: (&'a str,&'a str),
+   /// kinds of string literals
+   pub strings: Cow<'a,[StringRule<'a>]>,
+   /// token classes, [DEFAULT_CLASSES] if empty
+   pub classes: Cow<'a,[TokenClass<'a>]>,
+   /// symbols that switch to other states
+   pub transitions: Cow<'a,[Transition<'a>]>,
+   /// recognized numeric literals
+   pub numbers: NumberFormat,
+   /// whether the state returns text up to its next transition
+   pub text: bool,
+}
+
+/// Lexer driven by the [StateTable]s of its states, which can be static
+/// data generated from a [LexSpec](crate::LexSpec) or by the
+/// [lexer!](crate::lexer!) macro, so that creating a lexer neither compiles
+/// regular expressions nor allocates memory.  The lexer starts in the
+/// first state, and produces the same items as [StrTokenizer](crate::StrTokenizer),
+/// with byte-based column numbers.  Unlike StrTokenizer, an unrecognized
+/// character is a [RawToken::LexError] of its own, after which lexing
+/// continues.
+///
+/// Example:
+///```ignore
+///  mod lexer { include!(concat!(env!("OUT_DIR"), "/lexer.rs")); }
+///  let mut lexer = lexer::make_tokenizer("x = `a ${b} c`;");
+///  while let Some((token,line,column)) = lexer.next_token() {
+///    println!("{:?} in state {} at {}:{}",token,lexer.state_name(),line,column);
+///  }
+///```
+pub struct TableLexer<'a,'t>
+{
+   states: &'a [StateTable<'a>],
+   state: usize,
+   input: &'t str,
+   position: usize,
+   line: usize,
+   line_start: usize,
+   last_error: &'static str,
+   /// flag to toggle whether whitespaces are returned as Whitespace tokens,
+   /// default is false
+   pub keep_whitespace: bool,
+   /// flag to toggle whether line terminators are returned as Newline
+   /// tokens, default is false
+   pub keep_newline: bool,
+   /// flag to toggle whether comments are returned as Verbatim tokens,
+   /// default is false
+   pub keep_comment: bool,
+}
+impl<'a,'t> TableLexer<'a,'t>
+{
+  /// creates a lexer for the input with the given states, which must not
+  /// be empty, starting in the first state
+  pub fn new(states:&'a [StateTable<'a>], input:&'t str) -> TableLexer<'a,'t>
+  {
+    assert!(!states.is_empty(),"TableLexer requires at least one state");
+    TableLexer{states, state:0, input, position:0, line:1, line_start:0, last_error:"",
+               keep_whitespace:false, keep_newline:false, keep_comment:false}
+  }
+  /// the index of the current state
+  pub fn state(&self) -> usize {self.state}
+  /// the name of the current state
+  pub fn state_name(&self) -> &'a str {self.states[self.state].name}
+  /// switches to the state with the given index, if there is one
+  pub fn set_state(&mut self, state:usize) { if state<self.states.len() {self.state=state;} }
+  /// the entire input of the lexer
+  pub fn get_input(&self) -> &'t str {self.input}
+  /// the current byte position of the lexer
+  pub fn position(&self) -> usize {self.position}
+  /// the current line number
+  pub fn line(&self) -> usize {self.line}
+  /// the current column, in bytes
+  pub fn column(&self) -> usize {self.position-self.line_start+1}
+  /// returns the line of input that the lexer is currently on,
+  /// without its terminator
+  pub fn current_line(&self) -> &'t str
+  {
+    let rest = &self.input[self.line_start..];
+    let end = (0..rest.len()).find(|i| line_break_at(rest,*i,false).is_some()).unwrap_or(rest.len());
+    &rest[..end]
+  }
+  /// description of the most recent [RawToken::LexError], such as
+  /// "unclosed string", or the empty string
+  pub fn last_error(&self) -> &'static str {self.last_error}
+
+  // counts the line terminators between the given positions
+  fn count_lines(&mut self, from:usize, to:usize)
+  {
+    let mut i = from;
+    while i<to {
+      if let Some((_,n)) = line_break_at(self.input,i,false) {
+        self.line+=1; i+=n; self.line_start=i;
+      }
+      else {i+=1;}
+    }
+  }
+  // returns the token that spans from pi to end, counting its lines
+  fn token_to(&mut self, token:RawToken<'t>, pi:usize, end:usize) -> Option<(RawToken<'t>,usize,usize)>
+  {
+    let (line,column) = (self.line,pi-self.line_start+1);
+    self.position = end;
+    self.count_lines(pi,end);
+    Some((token,line,column))
+  }
+  fn error(&mut self, msg:&'static str, pi:usize, end:usize) -> Option<(RawToken<'t>,usize,usize)>
+  {
+    self.last_error = msg;
+    self.token_to(LexError,pi,end)
+  }
+
+  /// returns the next token with its line and column numbers, or None at
+  /// the end of the input
+  pub fn next_token(&mut self) -> Option<(RawToken<'t>,usize,usize)>
+  {
+    let states = self.states;
+    let st = &states[self.state];
+    let input = self.input;
+    while self.position<input.len()
+    {
+      let pi = self.position;
+      let rest = &input[pi..];
+      let c = rest.chars().next().unwrap();
+      let transition = st.transitions.iter().filter(|t|!t.on.is_empty() && rest.starts_with(t.on))
+                         .max_by_key(|t|t.on.len());
+
+      if st.text {
+        // text up to the next transition
+        let end = (pi..input.len()).find(|i| input.is_char_boundary(*i)
+                    && st.transitions.iter().any(|t| !t.on.is_empty() && input[*i..].starts_with(t.on)))
+                  .unwrap_or(input.len());
+        if end>pi {return self.token_to(Verbatim(&input[pi..end]),pi,end);}
+      }
+      else if c.is_whitespace() {
+        if let Some((_,n)) = line_break_at(input,pi,false) {
+          let column = pi-self.line_start+1;
+          self.position = pi+n; self.line+=1; self.line_start=self.position;
+          if self.keep_newline {return Some((Newline,self.line-1,column));}
+          continue;
+        }
+        let end = rest.find(|w:char| !w.is_whitespace() || w=='\n' || w=='\r').map(|n|pi+n).unwrap_or(input.len());
+        self.position = end;
+        if self.keep_whitespace {return Some((Whitespace(end-pi),self.line,pi-self.line_start+1));}
+        continue;
+      }
+
+      if let Some(t) = transition {
+        let end = pi+t.on.len();
+        self.state = t.to.min(self.states.len()-1);
+        return self.token_to(Symbol(&input[pi..end]),pi,end);
+      }
+
+      // comments
+      if !st.line_comment.is_empty() && rest.starts_with(st.line_comment) {
+        let len = (0..rest.len()).find(|i| line_break_at(rest,*i,false).is_some()).unwrap_or(rest.len());
+        self.position = pi+len;
+        if self.keep_comment {return self.token_to(Verbatim(&rest[..len]),pi,pi+len);}
+        continue;
+      }
+      let (cms,cme) = st.multiline_comment;
+      if !cms.is_empty() && rest.starts_with(cms) {
+        match rest[cms.len()..].find(cme) {
+          Some(n) => {
+            let end = pi+cms.len()+n+cme.len();
+            if self.keep_comment {return self.token_to(Verbatim(&input[pi..end]),pi,end);}
+            self.count_lines(pi,end);
+            self.position = end;
+            continue;
+          },
+          None => {return self.error("unclosed multi-line comment",pi,input.len());},
+        }
+      }
+
+      // string literals
+      if let Some(rule) = st.strings.iter().find(|r| !r.start.is_empty() && rest.starts_with(r.start)) {
+        let body = pi+rule.start.len();
+        let mut i = body;
+        while i<input.len() {
+          let s = &input[i..];
+          if rule.escape==Escape::Backslash && s.starts_with('\\') {
+            i+=1+s[1..].chars().next().map(|e|e.len_utf8()).unwrap_or(0);
+            continue;
+          }
+          if let Some(after) = s.strip_prefix(rule.end) {
+            if rule.escape==Escape::Doubled && after.starts_with(rule.end) {
+              i+=2*rule.end.len();
+              continue;
+            }
+            let end = i+rule.end.len();
+            return self.token_to(Strlit(&input[pi..end]),pi,end);
+          }
+          i+=s.chars().next().unwrap().len_utf8();
+        }
+        return self.error("unclosed string",pi,input.len());
+      }
+
+      // symbols
+      if let Some(d) = st.doubles.iter().find(|d| d.len()==2 && rest.starts_with(**d)) {
+        return self.token_to(Symbol(&rest[..2]),pi,pi+2);
+      }
+      if st.singles.contains(&c) {
+        return self.token_to(Symbol(&rest[..c.len_utf8()]),pi,pi+c.len_utf8());
+      }
+
+      // numbers
+      if let Some((num,len)) = scan_number(rest,&st.numbers) {
+        return match num {
+          Ok(token) => self.token_to(token,pi,pi+len),
+          Err(msg) => self.error(msg,pi,pi+len),
+        };
+      }
+
+      // token classes
+      let classes:&[TokenClass] = if st.classes.is_empty() {&DEFAULT_CLASSES} else {&st.classes};
+      if let Some(class) = classes.iter().find(|k| k.start.contains(c)) {
+        let len = rest.char_indices().skip(1).find(|(_,k)| !class.cont.contains(*k))
+                      .map(|(n,_)|n).unwrap_or(rest.len());
+        let text = &rest[..len];
+        let token = match class.kind {
+          TokenKind::Alphanum if st.keywords.contains(&text) => Keyword(text),
+          TokenKind::Alphanum => Alphanum(text),
+          TokenKind::Symbol => Symbol(text),
+          _ => Verbatim(text),
+        };
+        return self.token_to(token,pi,pi+len);
+      }
+
+      return self.error("unrecognized symbol",pi,pi+c.len_utf8());
+    }//while
+    None
+  }//next_token
+}//impl TableLexer
+
+impl<'a,'t> Iterator for TableLexer<'a,'t>
+{
+  type Item = (RawToken<'t>,usize,usize);
+  fn next(&mut self) -> Option<Self::Item> { self.next_token() }
+}
@@ -0,0 +1,260 @@
+//! Compact binary format for caching token streams.
+use std::io::{self,Read,Write,ErrorKind};
+use std::path::Path;
+use crate::{OwnedToken,StrTokenizer};
+
+// identifies the format, followed by its version
+const MAGIC:&[u8;4] = b"BLTK";
+const VERSION:u8 = 2;
+
+/// returns the 64-bit FNV-1a hash of a text, which is used by
+/// [TokenStream] to check that a cached stream belongs to the text
+pub fn content_hash(text:&str) -> u64
+{
+  let mut h:u64 = 0xcbf29ce484222325;
+  for b in text.bytes() {
+    h ^= b as u64;
+    h = h.wrapping_mul(0x100000001b3);
+  }
+  h
+}
+
+/// A token of a [TokenStream] with its position in the text.
+#[cfg_attr(feature = "serde", derive(serde::Serialize,serde::Deserialize))]
+#[derive(Clone,Debug,PartialEq,Eq,Hash)]
+pub struct TokenRecord
+{
+   /// the token
+   pub token: OwnedToken,
+   /// line number of the start of the token
+   pub line: usize,
+   /// column number (in bytes) of the start of the token
+   pub column: usize,
+   /// byte position of the start of the token
+   pub start: usize,
+   /// byte position after the end of the token
+   pub end: usize,
+}
+
+/// The tokens of a text along with the hash of the text and that of the
+/// rules of the tokenizer, which can be written to and read from a compact
+/// binary format, so that the tokens of files that have not changed need
+/// not be produced again.  Positions are stored as differences from the
+/// previous token, and integers as variable-length numbers.  With the
+/// `serde` feature, a TokenStream can also be serialized in any format
+/// supported by serde, such as JSON.
+///
+/// Example:
+///```ignore
+///  let text = std::fs::read_to_string("input.c").unwrap();
+///  let mut tokenizer = StrTokenizer::from_str(&text);
+///  tokenizer.add_double("==");
+///  let stream = match TokenStream::load("input.c.tokens", &tokenizer) {
+///    Some(cached) => cached,
+///    None => {
+///      let stream = TokenStream::from_tokenizer(&mut tokenizer);
+///      stream.save("input.c.tokens").unwrap();
+///      stream
+///    }
+///  };
+///```
+#[cfg_attr(feature = "serde", derive(serde::Serialize,serde::Deserialize))]
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct TokenStream
+{
+   /// [content_hash] of the text of the tokens, without trailing
+   /// whitespaces
+   pub hash: u64,
+   /// [StrTokenizer::config_hash] of the tokenizer that produced the tokens
+   pub config: u64,
+   /// the tokens, in order
+   pub tokens: Vec<TokenRecord>,
+}
+impl TokenStream
+{
+  /// reads all remaining tokens of a tokenizer, with the hash of its input
+  /// and of its rules
+  pub fn from_tokenizer(stk:&mut StrTokenizer) -> TokenStream
+  {
+    let hash = content_hash(stk.get_input().trim_end());
+    let config = stk.config_hash();
+    let mut tokens = Vec::new();
+    while let Some((token,line,column,start,_)) = stk.next_token_at() {
+      let end = stk.checkpoint().position();
+      tokens.push(TokenRecord{ token:token.into_owned(), line, column, start, end });
+    }
+    TokenStream{hash,config,tokens}
+  }
+  /// whether the stream was produced from the input of the given tokenizer
+  /// by a tokenizer with the same rules and settings, according to their
+  /// hashes.  Trailing whitespaces, which [StrTokenizer::set_input] trims,
+  /// are ignored.
+  pub fn is_valid_for(&self, stk:&StrTokenizer) -> bool
+  {
+    self.hash==content_hash(stk.get_input().trim_end()) && self.config==stk.config_hash()
+  }
+
+  /// writes the stream in binary format
+  pub fn write_to<W:Write>(&self, w:&mut W) -> io::Result<()>
+  {
+    w.write_all(&self.to_bytes())
+  }
+  /// encodes the stream in binary format
+  pub fn to_bytes(&self) -> Vec<u8>
+  {
+    let mut out = Vec::with_capacity(24+self.tokens.len()*4);
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    out.extend_from_slice(&self.hash.to_le_bytes());
+    out.extend_from_slice(&self.config.to_le_bytes());
+    put_varint(&mut out,self.tokens.len() as u64);
+    let (mut line,mut end) = (1,0);
+    for t in &self.tokens {
+      put_token(&mut out,&t.token);
+      put_varint(&mut out,t.line.wrapping_sub(line) as u64);
+      put_varint(&mut out,t.column as u64);
+      put_varint(&mut out,t.start.wrapping_sub(end) as u64);
+      put_varint(&mut out,t.end.wrapping_sub(t.start) as u64);
+      line = t.line; end = t.end;
+    }
+    out
+  }
+  /// reads a stream in binary format
+  pub fn read_from<R:Read>(r:&mut R) -> io::Result<TokenStream>
+  {
+    let mut bytes = Vec::new();
+    r.read_to_end(&mut bytes)?;
+    TokenStream::from_bytes(&bytes)
+  }
+  /// decodes a stream in binary format, returning an error of kind
+  /// [ErrorKind::InvalidData] if the bytes are not of that format
+  pub fn from_bytes(bytes:&[u8]) -> io::Result<TokenStream>
+  {
+    let mut d = Decoder{bytes,pos:0};
+    if d.take(4)?!=MAGIC || d.byte()?!=VERSION {return Err(invalid("not a token stream"));}
+    let hash = d.u64()?;
+    let config = d.u64()?;
+    let count = d.varint()? as usize;
+    let mut tokens = Vec::with_capacity(count.min(bytes.len()));
+    let (mut line,mut end) = (1usize,0usize);
+    for _ in 0..count {
+      let token = d.token()?;
+      let tline = line.wrapping_add(d.varint()? as usize);
+      let column = d.varint()? as usize;
+      let start = end.wrapping_add(d.varint()? as usize);
+      let tend = start.wrapping_add(d.varint()? as usize);
+      tokens.push(TokenRecord{token,line:tline,column,start,end:tend});
+      line = tline; end = tend;
+    }
+    if d.pos!=bytes.len() {return Err(invalid("trailing bytes after token stream"));}
+    Ok(TokenStream{hash,config,tokens})
+  }
+
+  /// writes the stream in binary format to a file
+  pub fn save<P:AsRef<Path>>(&self, path:P) -> io::Result<()>
+  {
+    std::fs::write(path,self.to_bytes())
+  }
+  /// reads a stream in binary format from a file, and returns it only if
+  /// it is valid for the given tokenizer (see [TokenStream::is_valid_for]).
+  /// Returns None if the file cannot be read or is not valid.
+  pub fn load<P:AsRef<Path>>(path:P, stk:&StrTokenizer) -> Option<TokenStream>
+  {
+    let bytes = std::fs::read(path).ok()?;
+    TokenStream::from_bytes(&bytes).ok().filter(|s| s.is_valid_for(stk))
+  }
+}//impl TokenStream
+
+fn invalid(msg:&str) -> io::Error { io::Error::new(ErrorKind::InvalidData,msg) }
+
+// unsigned LEB128
+fn put_varint(out:&mut Vec<u8>, mut n:u64)
+{
+  while n>=0x80 {
+    out.push((n as u8)|0x80);
+    n >>= 7;
+  }
+  out.push(n as u8);
+}
+fn put_str(out:&mut Vec<u8>, s:&str)
+{
+  put_varint(out,s.len() as u64);
+  out.extend_from_slice(s.as_bytes());
+}
+fn put_token(out:&mut Vec<u8>, token:&OwnedToken)
+{
+  match token {
+    OwnedToken::Num(n) => { out.push(0); put_varint(out,((n<<1)^(n>>63)) as u64); }, // zigzag
+    OwnedToken::Float(f) => { out.push(1); out.extend_from_slice(&f.to_bits().to_le_bytes()); },
+    OwnedToken::Char(c) => { out.push(2); put_varint(out,*c as u64); },
+    OwnedToken::Strlit(s) => { out.push(3); put_str(out,s); },
+    OwnedToken::Alphanum(s) => { out.push(4); put_str(out,s); },
+    OwnedToken::Keyword(s) => { out.push(5); put_str(out,s); },
+    OwnedToken::Symbol(s) => { out.push(6); put_str(out,s); },
+    OwnedToken::Newline => { out.push(7); },
+    OwnedToken::Whitespace(n) => { out.push(8); put_varint(out,*n as u64); },
+    OwnedToken::WhitespaceText(s,n) => { out.push(9); put_str(out,s); put_varint(out,*n as u64); },
+    OwnedToken::Verbatim(s) => { out.push(10); put_str(out,s); },
+    OwnedToken::LexError => { out.push(11); },
+    OwnedToken::RegexLit(s,f) => { out.push(12); put_str(out,s); put_str(out,f); },
+    OwnedToken::ReaderMacro(s) => { out.push(13); put_str(out,s); },
+  }
+}
+
+struct Decoder<'b>
+{
+   bytes: &'b [u8],
+   pos: usize,
+}
+impl<'b> Decoder<'b>
+{
+  fn take(&mut self, n:usize) -> io::Result<&'b [u8]>
+  {
+    if n>self.bytes.len()-self.pos {return Err(invalid("truncated token stream"));}
+    self.pos += n;
+    Ok(&self.bytes[self.pos-n..self.pos])
+  }
+  fn byte(&mut self) -> io::Result<u8> { Ok(self.take(1)?[0]) }
+  fn u64(&mut self) -> io::Result<u64>
+  {
+    let mut b = [0u8;8];
+    b.copy_from_slice(self.take(8)?);
+    Ok(u64::from_le_bytes(b))
+  }
+  fn varint(&mut self) -> io::Result<u64>
+  {
+    let mut n:u64 = 0;
+    for shift in (0..64).step_by(7) {
+      let b = self.byte()?;
+      n |= ((b&0x7f) as u64) << shift;
+      if b<0x80 {return Ok(n);}
+    }
+    Err(invalid("malformed number in token stream"))
+  }
+  fn string(&mut self) -> io::Result<String>
+  {
+    let n = self.varint()? as usize;
+    let bytes = self.take(n)?;
+    String::from_utf8(bytes.to_vec()).map_err(|_| invalid("malformed string in token stream"))
+  }
+  fn token(&mut self) -> io::Result<OwnedToken>
+  {
+    Ok(match self.byte()? {
+      0 => { let z = self.varint()?; OwnedToken::Num(((z>>1) as i64)^-((z&1) as i64)) },
+      1 => OwnedToken::Float(f64::from_bits(self.u64()?)),
+      2 => OwnedToken::Char(char::from_u32(self.varint()? as u32).ok_or_else(|| invalid("malformed char in token stream"))?),
+      3 => OwnedToken::Strlit(self.string()?),
+      4 => OwnedToken::Alphanum(self.string()?),
+      5 => OwnedToken::Keyword(self.string()?),
+      6 => OwnedToken::Symbol(self.string()?),
+      7 => OwnedToken::Newline,
+      8 => OwnedToken::Whitespace(self.varint()? as usize),
+      9 => { let s = self.string()?; OwnedToken::WhitespaceText(s,self.varint()? as usize) },
+      10 => OwnedToken::Verbatim(self.string()?),
+      11 => OwnedToken::LexError,
+      12 => { let s = self.string()?; OwnedToken::RegexLit(s,self.string()?) },
+      13 => OwnedToken::ReaderMacro(self.string()?),
+      _ => return Err(invalid("unknown token in token stream")),
+    })
+  }
+}//impl Decoder
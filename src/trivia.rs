@@ -0,0 +1,136 @@
+//! Lossless tokenization that preserves whitespaces and comments.
+use crate::{RawToken,StrTokenizer,LexerDef,LexSource};
+
+/// A significant token produced by [LosslessTokenizer], along with the
+/// exact text of the whitespaces, newlines and comments (the *trivia*)
+/// around it.  Concatenating `leading`, `text` and `trailing` of every
+/// token, in order, reproduces the input byte for byte.
+#[derive(Debug)]
+pub struct TriviaToken<'t>
+{
+   /// trivia before the token that is not trailing trivia of the previous
+   /// token, including whole lines of comments above it
+   pub leading: &'t str,
+   /// the token, or None for the last item, which holds the trivia at the
+   /// end of the input
+   pub token: Option<RawToken<'t>>,
+   /// the exact text of the token, such as `0x1F` for `Num(31)`
+   pub text: &'t str,
+   /// trivia after the token on the same line, up to and including the
+   /// first newline
+   pub trailing: &'t str,
+   /// line number of the start of the token
+   pub line: usize,
+   /// column number of the start of the token
+   pub column: usize,
+   /// byte position of the start of the token
+   pub position: usize,
+}
+
+/// Tokenizer for formatters and other tools that must reproduce their input
+/// exactly.  Trailing whitespaces are not trimmed from the input, and every
+/// [RawToken::Whitespace], [RawToken::Newline] and [RawToken::Verbatim]
+/// comment is attached, as exact text, to a neighboring significant token
+/// in a [TriviaToken].  Tabs, spaces and `\r\n` line endings are therefore
+/// all preserved.  The last item has no token, and holds the trivia at the
+/// end of the input.
+///
+/// Example:
+///```ignore
+///  let mut def = LexerDef::new();
+///  def.add_double("==");
+///  let mut output = String::new();
+///  for t in LosslessTokenizer::new(&def, input) {
+///    output.push_str(t.leading);
+///    output.push_str(t.text);
+///    output.push_str(t.trailing);
+///  }
+///  assert_eq!(output, input);
+///```
+pub struct LosslessTokenizer<'t>
+{
+   tokenizer: StrTokenizer<'t>,
+   peeked: Option<(RawToken<'t>,usize,usize,usize)>, // token,line,column,start
+   emitted: usize, // position up to which the input has been returned
+   done: bool,
+}
+impl<'t> LosslessTokenizer<'t>
+{
+  /// creates a lossless tokenizer for the given input, with the rules of
+  /// the given [LexerDef]
+  pub fn new(def:&'t LexerDef<'t>, input:&'t str) -> LosslessTokenizer<'t>
+  {
+    let mut tokenizer = StrTokenizer::with_def(def);
+    tokenizer.set_untrimmed_input(input);
+    tokenizer.keep_whitespace = true;
+    tokenizer.keep_newline = true;
+    tokenizer.keep_comment = true;
+    LosslessTokenizer { tokenizer, peeked:None, emitted:0, done:false }
+  }
+  /// creates a lossless tokenizer for the contents of a [LexSource],
+  /// with the rules of the given [LexerDef]
+  pub fn from_source(def:&'t LexerDef<'t>, ls:&'t LexSource<'t>) -> LosslessTokenizer<'t>
+  {
+    let mut lt = LosslessTokenizer::new(def,ls.get_contents());
+    lt.tokenizer.set_source(ls.get_path());
+    lt.tokenizer.set_source_id(ls.get_id());
+    lt
+  }
+  /// the underlying tokenizer, for instance to call
+  /// [StrTokenizer::error_diagnostic]
+  pub fn tokenizer(&self) -> &StrTokenizer<'t> {&self.tokenizer}
+
+  // the next raw token along with its line, column and start position
+  fn next_raw(&mut self) -> Option<(RawToken<'t>,usize,usize,usize)>
+  {
+    if let Some(p) = self.peeked.take() {return Some(p);}
+    let start = self.tokenizer.position();
+    self.tokenizer.next_token().map(|(t,l,c)| (t,l,c,start))
+  }
+
+  /// returns the next significant token with its trivia, or None after
+  /// the item that holds the trivia at the end of the input
+  pub fn next_token(&mut self) -> Option<TriviaToken<'t>>
+  {
+    if self.done {return None;}
+    let input = self.tokenizer.get_input();
+    let leadstart = self.emitted;
+    let mut found = None;
+    while let Some((tok,line,column,start)) = self.next_raw() {
+      if !tok.is_trivia() { found = Some((tok,line,column,start)); break; }
+    }
+    let (token,line,column,start) = match found {
+      Some(f) => f,
+      None => {
+        self.done = true;
+        let (line,column) = (self.tokenizer.line(),self.tokenizer.column());
+        self.emitted = input.len();
+        return Some(TriviaToken { leading:&input[leadstart..], token:None, text:"",
+          trailing:"", line, column, position:input.len() });
+      },
+    };
+    let end = self.tokenizer.position();
+    // trailing trivia extends to the end of the line
+    let mut trailend = end;
+    while let Some((tok,l,c,s)) = self.next_raw() {
+      if !tok.is_trivia() { self.peeked = Some((tok,l,c,s)); break; }
+      trailend = self.tokenizer.position();
+      if let RawToken::Newline = tok {break;}
+      if let RawToken::Verbatim(v) = tok { if v.contains(['\n','\r']) {break;} }
+    }
+    self.emitted = trailend;
+    Some(TriviaToken {
+      leading: &input[leadstart..start],
+      token: Some(token),
+      text: &input[start..end],
+      trailing: &input[end..trailend],
+      line, column, position:start,
+    })
+  }//next_token
+}//impl LosslessTokenizer
+
+impl<'t> Iterator for LosslessTokenizer<'t>
+{
+  type Item = TriviaToken<'t>;
+  fn next(&mut self) -> Option<TriviaToken<'t>> { self.next_token() }
+}
@@ -8,17 +8,27 @@
 #![allow(unused_doc_comments)]
 #![allow(unused_imports)]
 use regex::Regex;
-use std::collections::{HashSet};
+use std::collections::{HashSet,VecDeque};
+use std::sync::OnceLock;
+use std::borrow::Cow;
+use std::hash::{Hash,Hasher};
 use crate::RawToken::*;
+use crate::{Diagnostic,HookContext,content_hash};
+use crate::hooks::{LexHook,run_hooks};
 
-/// structure produced by [StrTokenizer].
-#[derive(Debug)]
+/// structure produced by [StrTokenizer].  Tokens can be compared and
+/// hashed; [RawToken::Float]s are equal if they have the same bits, so
+/// that `Float(f64::NAN)` equals itself but `Float(0.0)` differs from
+/// `Float(-0.0)`.
+#[derive(Debug,Clone)]
+#[non_exhaustive]
 pub enum RawToken<'t>
 {
   /// an unsigned integer, though for convenience it is interpreted as
   /// a signed number.  Negative numbers must be recognized by higher-level
-  /// parser.  Both decimal and hexadecimal numbers prefixed by 0x are
-  /// recognized.
+  /// parser, unless '-' can start an alphanumeric sequence (see
+  /// [LexerDef::set_identifier_chars]).  Both decimal and hexadecimal
+  /// numbers prefixed by 0x are recognized.
   Num(i64),
 //  Hex(u64),
   /// floating point number
@@ -30,6 +40,9 @@ pub enum RawToken<'t>
   /// Alphanumeric sequence, staring with an alphabetical character or '_',
   /// and followed by arbitrary numbers of alphabetical, numeric or _.
   Alphanum(&'t str),
+  /// Alphanumeric sequence that was added as a keyword with
+  /// [StrTokenizer::add_keywords]
+  Keyword(&'t str),
   /// non-alphanumeric character, either identified as doubles, singles, or
   /// unrecognized sequences.
   Symbol(&'t str),
@@ -37,85 +50,846 @@ pub enum RawToken<'t>
   Newline,
   /// number of consecutive whitespaces, returned optionally
   Whitespace(usize), // counts number of non-newline whitespaces
+  /// consecutive whitespaces along with their visual width, returned
+  /// instead of [RawToken::Whitespace] if [StrTokenizer::whitespace_text]
+  /// is set.  See [StrTokenizer::set_tab_width].
+  WhitespaceText(&'t str,usize),
   /// usually used to represent comments, if returned optionally
   Verbatim(&'t str),
+  /// regular expression literal such as `/a+b/gi`, with its pattern
+  /// between the slashes and its flags, recognized only if enabled with
+  /// [LexerDef::set_regex_literals]
+  RegexLit(&'t str,&'t str),
+  /// reader macro such as Lisp's `'` (quote), `` ` `` (quasiquote), `,`
+  /// (unquote) or `,@` (unquote-splicing), recognized only if added with
+  /// [LexerDef::add_reader_macros]
+  ReaderMacro(&'t str),
   /// tokenizer error
   LexError,
 }//RawToken
 
+impl<'t> PartialEq for RawToken<'t>
+{
+  fn eq(&self, other:&RawToken<'t>) -> bool
+  {
+    match (self,other) {
+      (Num(a),Num(b)) => a==b,
+      (Float(a),Float(b)) => a.to_bits()==b.to_bits(),
+      (Char(a),Char(b)) => a==b,
+      (Strlit(a),Strlit(b)) | (Alphanum(a),Alphanum(b)) | (Keyword(a),Keyword(b))
+        | (Symbol(a),Symbol(b)) | (Verbatim(a),Verbatim(b))
+        | (ReaderMacro(a),ReaderMacro(b)) => a==b,
+      (Whitespace(a),Whitespace(b)) => a==b,
+      (WhitespaceText(a,n),WhitespaceText(b,m)) => a==b && n==m,
+      (RegexLit(a,f),RegexLit(b,g)) => a==b && f==g,
+      (Newline,Newline) | (LexError,LexError) => true,
+      _ => false,
+    }
+  }
+}
+impl<'t> Eq for RawToken<'t> {}
+impl<'t> Hash for RawToken<'t>
+{
+  fn hash<H:Hasher>(&self, state:&mut H)
+  {
+    std::mem::discriminant(self).hash(state);
+    match self {
+      Num(n) => n.hash(state),
+      Float(f) => f.to_bits().hash(state),
+      Char(c) => c.hash(state),
+      Strlit(s) | Alphanum(s) | Keyword(s) | Symbol(s) | Verbatim(s)
+        | ReaderMacro(s) => s.hash(state),
+      Whitespace(n) => n.hash(state),
+      WhitespaceText(s,n) => { s.hash(state); n.hash(state); },
+      RegexLit(s,f) => { s.hash(state); f.hash(state); },
+      Newline | LexError => {},
+    }
+  }
+}
+
+impl<'t> RawToken<'t>
+{
+  /// whether the token is a [RawToken::Whitespace], [RawToken::Newline]
+  /// or [RawToken::Verbatim] comment, which carry no meaning for most parsers
+  pub fn is_trivia(&self) -> bool
+  {
+    matches!(self, Whitespace(_) | WhitespaceText(_,_) | Newline | Verbatim(_))
+  }
+  /// the kind of the token, without its value
+  pub fn kind(&self) -> TokenKind
+  {
+    match self {
+      Num(_) => TokenKind::Num,
+      Float(_) => TokenKind::Float,
+      Char(_) => TokenKind::Char,
+      Strlit(_) => TokenKind::Strlit,
+      Alphanum(_) => TokenKind::Alphanum,
+      Keyword(_) => TokenKind::Keyword,
+      Symbol(_) => TokenKind::Symbol,
+      Newline => TokenKind::Newline,
+      Whitespace(_) => TokenKind::Whitespace,
+      WhitespaceText(_,_) => TokenKind::WhitespaceText,
+      Verbatim(_) => TokenKind::Verbatim,
+      RegexLit(_,_) => TokenKind::RegexLit,
+      ReaderMacro(_) => TokenKind::ReaderMacro,
+      LexError => TokenKind::LexError,
+    }
+  }
+  /// converts the token into an [OwnedToken] that does not borrow from
+  /// the input
+  pub fn into_owned(self) -> OwnedToken
+  {
+    match self {
+      Num(n) => OwnedToken::Num(n),
+      Float(f) => OwnedToken::Float(f),
+      Char(c) => OwnedToken::Char(c),
+      Strlit(s) => OwnedToken::Strlit(s.to_owned()),
+      Alphanum(s) => OwnedToken::Alphanum(s.to_owned()),
+      Keyword(s) => OwnedToken::Keyword(s.to_owned()),
+      Symbol(s) => OwnedToken::Symbol(s.to_owned()),
+      Newline => OwnedToken::Newline,
+      Whitespace(n) => OwnedToken::Whitespace(n),
+      WhitespaceText(s,n) => OwnedToken::WhitespaceText(s.to_owned(),n),
+      Verbatim(s) => OwnedToken::Verbatim(s.to_owned()),
+      RegexLit(s,f) => OwnedToken::RegexLit(s.to_owned(),f.to_owned()),
+      ReaderMacro(s) => OwnedToken::ReaderMacro(s.to_owned()),
+      LexError => OwnedToken::LexError,
+    }
+  }
+}
+
+/// Version of [RawToken] that owns its strings, and can therefore outlive
+/// the input it was produced from, and be stored in syntax trees.  The
+/// variants correspond to those of [RawToken], and tokens are compared
+/// and hashed in the same way.  The [Token](crate::Token)s of
+/// [File_tokenizer](crate::File_tokenizer) can also be converted into
+/// OwnedTokens with [TryFrom](std::convert::TryFrom).
+///
+/// Example:
+///```ignore
+///  let tokens:Vec<OwnedToken> = StrTokenizer::from_str("x = 1")
+///                               .map(|(t,_,_)| t.into_owned()).collect();
+///  assert_eq!(tokens[0], OwnedToken::Alphanum("x".to_owned()));
+///  assert_eq!(tokens[2].as_raw(), RawToken::Num(1));
+///```
+#[cfg_attr(feature = "serde", derive(serde::Serialize,serde::Deserialize))]
+#[derive(Debug,Clone)]
+#[non_exhaustive]
+pub enum OwnedToken
+{
+  /// see [RawToken::Num]
+  Num(i64),
+  /// see [RawToken::Float]
+  Float(f64),
+  /// see [RawToken::Char]
+  Char(char),
+  /// see [RawToken::Strlit]
+  Strlit(String),
+  /// see [RawToken::Alphanum]
+  Alphanum(String),
+  /// see [RawToken::Keyword]
+  Keyword(String),
+  /// see [RawToken::Symbol]
+  Symbol(String),
+  /// see [RawToken::Newline]
+  Newline,
+  /// see [RawToken::Whitespace]
+  Whitespace(usize),
+  /// see [RawToken::WhitespaceText]
+  WhitespaceText(String,usize),
+  /// see [RawToken::Verbatim]
+  Verbatim(String),
+  /// see [RawToken::RegexLit]
+  RegexLit(String,String),
+  /// see [RawToken::ReaderMacro]
+  ReaderMacro(String),
+  /// see [RawToken::LexError]
+  LexError,
+}//OwnedToken
+
+impl PartialEq for OwnedToken
+{
+  fn eq(&self, other:&OwnedToken) -> bool { self.as_raw()==other.as_raw() }
+}
+impl Eq for OwnedToken {}
+impl Hash for OwnedToken
+{
+  fn hash<H:Hasher>(&self, state:&mut H) { self.as_raw().hash(state); }
+}
+impl<'t> From<RawToken<'t>> for OwnedToken
+{
+  fn from(token:RawToken<'t>) -> OwnedToken { token.into_owned() }
+}
+impl std::convert::TryFrom<crate::Token> for OwnedToken
+{
+  type Error = crate::Token;
+  /// converts a [Token](crate::Token) of [File_tokenizer](crate::File_tokenizer),
+  /// as by [Token::as_raw](crate::Token::as_raw).  The hidden Nothing token
+  /// cannot be converted, and is returned as the error.
+  fn try_from(token:crate::Token) -> Result<OwnedToken,crate::Token>
+  {
+    match token.as_raw() {
+      Some(raw) => Ok(raw.into_owned()),
+      None => Err(token),
+    }
+  }
+}
+
+impl OwnedToken
+{
+  /// returns a [RawToken] that borrows the strings of this token
+  pub fn as_raw(&self) -> RawToken<'_>
+  {
+    match self {
+      OwnedToken::Num(n) => Num(*n),
+      OwnedToken::Float(f) => Float(*f),
+      OwnedToken::Char(c) => Char(*c),
+      OwnedToken::Strlit(s) => Strlit(s),
+      OwnedToken::Alphanum(s) => Alphanum(s),
+      OwnedToken::Keyword(s) => Keyword(s),
+      OwnedToken::Symbol(s) => Symbol(s),
+      OwnedToken::Newline => Newline,
+      OwnedToken::Whitespace(n) => Whitespace(*n),
+      OwnedToken::WhitespaceText(s,n) => WhitespaceText(s,*n),
+      OwnedToken::Verbatim(s) => Verbatim(s),
+      OwnedToken::RegexLit(s,f) => RegexLit(s,f),
+      OwnedToken::ReaderMacro(s) => ReaderMacro(s),
+      OwnedToken::LexError => LexError,
+    }
+  }
+  /// the kind of the token, without its value
+  pub fn kind(&self) -> TokenKind { self.as_raw().kind() }
+}
+
+/// The kinds of tokens, without their values, as returned by
+/// [StrTokenizer::peek_kind] and [File_tokenizer::peek_kind](crate::File_tokenizer::peek_kind).
+/// The kinds correspond to the variants of [RawToken]; the [Token](crate::Token)s
+/// of [File_tokenizer](crate::File_tokenizer) are mapped to the kind of the
+/// corresponding RawToken.
+#[cfg_attr(feature = "serde", derive(serde::Serialize,serde::Deserialize))]
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+#[non_exhaustive]
+pub enum TokenKind
+{
+  Num,
+  Float,
+  Char,
+  Strlit,
+  Alphanum,
+  Keyword,
+  Symbol,
+  Newline,
+  Whitespace,
+  WhitespaceText,
+  Verbatim,
+  RegexLit,
+  ReaderMacro,
+  LexError,
+}
+
+/// The line terminators recognized by the tokenizers.  A `\r` that is
+/// immediately followed by `\n` forms a single [LineEnding::CrLf].
+#[cfg_attr(feature = "serde", derive(serde::Serialize,serde::Deserialize))]
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub enum LineEnding
+{
+  /// `\n`, as on Unix
+  Lf,
+  /// `\r\n`, as on Windows
+  CrLf,
+  /// lone `\r`, as on classic Mac OS
+  Cr,
+  /// U+2028, only recognized if enabled
+  LineSeparator,
+  /// U+2029, only recognized if enabled
+  ParagraphSeparator,
+}
+impl LineEnding
+{
+  /// the text of the line terminator
+  pub fn as_str(&self) -> &'static str
+  {
+    match self {
+      LineEnding::Lf => "\n",
+      LineEnding::CrLf => "\r\n",
+      LineEnding::Cr => "\r",
+      LineEnding::LineSeparator => "\u{2028}",
+      LineEnding::ParagraphSeparator => "\u{2029}",
+    }
+  }
+}
+
+// the line terminator that starts at byte i of s, if any, with its length.
+// U+2028 and U+2029 are recognized only if unicode is true.
+pub(crate) fn line_break_at(s:&str, i:usize, unicode:bool) -> Option<(LineEnding,usize)>
+{
+  let b = s.as_bytes();
+  match b.get(i)? {
+    b'\n' => Some((LineEnding::Lf,1)),
+    b'\r' if b.get(i+1)==Some(&b'\n') => Some((LineEnding::CrLf,2)),
+    b'\r' => Some((LineEnding::Cr,1)),
+    0xE2 if unicode && b.get(i+1)==Some(&0x80) => match b.get(i+2) {
+      Some(0xA8) => Some((LineEnding::LineSeparator,3)),
+      Some(0xA9) => Some((LineEnding::ParagraphSeparator,3)),
+      _ => None,
+    },
+    _ => None,
+  }
+}
+
+// the position, kind and length of the first line terminator of s at or
+// after byte from
+pub(crate) fn find_line_break(s:&str, from:usize, unicode:bool) -> Option<(usize,LineEnding,usize)>
+{
+  let b = s.as_bytes();
+  let mut i = from;
+  while i<b.len() {
+    match b[i] {
+      b'\n' | b'\r' | 0xE2 => {
+        if let Some((e,n)) = line_break_at(s,i,unicode) {return Some((i,e,n));}
+      },
+      _ => {},
+    }
+    i+=1;
+  }
+  None
+}
+
+
+/// The state of a [StrTokenizer] between two tokens: its byte position in
+/// the input, its line number and the position at which that line starts,
+/// along with the last line terminator, the last error and the kind and
+/// position of the previous token that is not trivia (see
+/// [StrTokenizer::previous]).  No other state is kept between tokens, since
+/// comments and string literals are always scanned as whole tokens.  A
+/// LexState is a small [Copy] value, so that saving it with
+/// [StrTokenizer::checkpoint] is cheap.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub struct LexState
+{
+   pub(crate) position: usize,
+   pub(crate) line: usize,
+   pub(crate) line_start: usize,
+   pub(crate) line_ending: Option<LineEnding>,
+   pub(crate) last_error: &'static str,
+   pub(crate) error_span: (usize,usize),
+   pub(crate) previous: Option<(TokenKind,usize,usize)>,
+}
+impl LexState
+{
+  /// the state at the start of an input
+  pub fn start() -> LexState
+  {
+    LexState{position:0, line:1, line_start:0, line_ending:None, last_error:"", error_span:(0,0), previous:None}
+  }
+  /// byte position in the input
+  pub fn position(&self) -> usize {self.position}
+  /// line number
+  pub fn line(&self) -> usize {self.line}
+  /// byte position at which the line starts
+  pub fn line_start(&self) -> usize {self.line_start}
+}
+
+static DECUINT:OnceLock<Regex> = OnceLock::new();
+static HEXNUM:OnceLock<Regex> = OnceLock::new();
+static FLOATP:OnceLock<Regex> = OnceLock::new();
+static ALPHAN:OnceLock<Regex> = OnceLock::new();
+static NONALPH:OnceLock<Regex> = OnceLock::new();
+
+/// The rules of a tokenizer: its symbols, keywords and comment markers.
+/// A LexerDef is separate from the input and position information kept
+/// by [StrTokenizer], so one definition can be shared by many tokenizers.
+/// A `LexerDef<'static>` is [Send] and [Sync], and can be shared across
+/// threads in an [std::sync::Arc]:
+///```ignore
+///  let mut def = LexerDef::new();
+///  def.add_double("==");
+///  def.add_keywords("if else while");
+///  let def = std::sync::Arc::new(def);
+///  for path in paths {
+///    let def = std::sync::Arc::clone(&def);
+///    std::thread::spawn(move || {
+///      let source = LexSource::new(path).unwrap();
+///      let tokens:Vec<_> = def.tokenizer(source.get_contents()).collect();
+///    });
+///  }
+///```
+/// The same functions that configure a definition are also available
+/// on [StrTokenizer].  A tokenizer created with [StrTokenizer::with_def]
+/// borrows the definition, and makes its own copy only if it is changed.
+#[derive(Clone,Debug)]
+pub struct LexerDef<'t>
+{
+   doubles:HashSet<&'t str>,
+   singles:HashSet<char>,
+   keywords:HashSet<&'t str>,
+   line_comment:&'t str,
+   ml_comment_start:&'t str,
+   ml_comment_end:&'t str,
+   string_delimiters:Vec<char>,
+   backslash_escapes:bool,
+   triple_quotes:bool,
+   char_literals:bool,
+   regex_literals:bool,
+   operand_keywords:HashSet<&'t str>,
+   numbers:NumberFormat,
+   keywords_ignore_case:bool,
+   nested_comments:bool,
+   ident_start:Vec<char>,
+   ident_continue:Vec<char>,
+   reader_macros:HashSet<&'t str>,
+   pub(crate) hooks:Vec<LexHook<'t>>,
+}
+impl<'t> Default for LexerDef<'t>
+{
+  fn default() -> Self { LexerDef::new() }
+}
+impl<'t> LexerDef<'t>
+{
+  /// creates a definition with the defaults: the single-character symbols
+  /// ( ) [ ] { }, line comments starting with "//" and multi-line comments
+  /// delineated by "/\*" and "\*/".
+  pub fn new() -> LexerDef<'t>
+  {
+    let mut singles = HashSet::with_capacity(16);
+    for c in ['(',')','[',']','{','}'] {singles.insert(c);}
+    LexerDef {
+      doubles: HashSet::with_capacity(16),
+      singles,
+      keywords: HashSet::new(),
+      line_comment: "//",
+      ml_comment_start: "/*",
+      ml_comment_end: "*/",
+      string_delimiters: vec!['"'],
+      backslash_escapes: true,
+      triple_quotes: false,
+      char_literals: true,
+      regex_literals: false,
+      operand_keywords: "this super true false null".split_whitespace().collect(),
+      numbers: NumberFormat::default(),
+      keywords_ignore_case: false,
+      nested_comments: false,
+      ident_start: Vec::new(),
+      ident_continue: Vec::new(),
+      reader_macros: HashSet::new(),
+      hooks: Vec::new(),
+    }
+  }
+  /// adds a symbol of exactly length two. If the length is not two the function
+  /// has no effect.  Note that these symbols override all other types except for
+  /// leading whitespaces and comments markers, e.g. "//" will have precedence
+  /// over "/" and "==" will have precedence over "=".
+  pub fn add_double(&mut self, s:&'t str)
+  {
+    if s.len()==2 { self.doubles.insert(s); }
+  }
+  /// add a single-character symbol.  The type of the symbol overrides other
+  /// types except for whitespaces, comments and double-character symbols.
+  pub fn add_single(&mut self, c:char) { self.singles.insert(c);}
+  /// adds keywords, given as a whitespace-separated string such as
+  /// "if else while".  Alphanumeric sequences that are keywords are
+  /// returned as [RawToken::Keyword] instead of [RawToken::Alphanum].
+  pub fn add_keywords(&mut self, kws:&'t str)
+  {
+    for kw in kws.split_whitespace() { self.keywords.insert(kw); }
+  }
+  /// adds reader macros, given as a whitespace-separated string such as
+  /// "' ` , ,@".  They are returned as [RawToken::ReaderMacro] instead of
+  /// symbols, the longest one that matches first, and take precedence over
+  /// doubles, singles and char literals, but not over comments.
+  pub fn add_reader_macros(&mut self, ms:&'t str)
+  {
+    for m in ms.split_whitespace() { self.reader_macros.insert(m); }
+  }
+  /// sets the symbol that begins a single-line comment. The default is
+  /// "//".  If this is set to the empty string then no line-comments are
+  /// recognized.
+  pub fn set_line_comment(&mut self,cm:&'t str) {
+    self.line_comment=cm;
+  }
+  /// sets the symbols used to delineate multi-line comments using a
+  /// whitespace separated string such as "/* */".  These symbols are
+  /// also the default.  Set this to the empty string to disable
+  /// multi-line comments.
+  pub fn set_multiline_comments(&mut self,cm:&'t str)
+  {
+    if cm.is_empty() {
+      self.ml_comment_start=""; self.ml_comment_end=""; return;
+    }
+    let split:Vec<_> = cm.split_whitespace().collect();
+    if split.len()!=2 {return;}
+    self.ml_comment_start = split[0].trim();
+    self.ml_comment_end = split[1].trim();
+  }
+  /// sets the start and end symbols of multi-line comments separately.
+  /// The function has no effect if either symbol is empty.
+  pub fn set_multiline_comment_delimiters(&mut self, start:&'t str, end:&'t str)
+  {
+    if start.is_empty() || end.is_empty() {return;}
+    self.ml_comment_start = start;
+    self.ml_comment_end = end;
+  }
+  /// sets the characters that delimit string literals, such as "\"'".
+  /// The default is the double quote only.  Non-ascii characters are
+  /// ignored.
+  pub fn set_string_delimiters(&mut self, delims:&str)
+  {
+    self.string_delimiters = delims.chars().filter(|c|c.is_ascii()).collect();
+  }
+  /// sets whether a backslash escapes the next character in string
+  /// literals, as in C.  If false, a delimiter is escaped by doubling it,
+  /// as in SQL ('it''s').  The default is true.
+  pub fn set_backslash_escapes(&mut self, b:bool) {self.backslash_escapes=b;}
+  /// sets whether a string that starts with three delimiters, as in
+  /// Python's """...""", ends at the next three delimiters.  The default
+  /// is false.
+  pub fn set_triple_quotes(&mut self, b:bool) {self.triple_quotes=b;}
+  /// sets whether a single character in single quotes, such as 'a' or
+  /// '\n', is recognized as a [RawToken::Char].  The default is true.
+  pub fn set_char_literals(&mut self, b:bool) {self.char_literals=b;}
+  /// sets whether a '/' that can start an operand, as in JavaScript,
+  /// begins a [RawToken::RegexLit] such as `/[a-z]+\/x/gi`, which ends at
+  /// the next '/' that is neither escaped nor inside brackets, and is
+  /// followed by its flags, if any.  A '/' starts a regular expression at
+  /// the start of the input and after a keyword or a symbol other than ')',
+  /// ']', "++" and "--", and is a division after any other token, such as
+  /// an alphanumeric sequence or a number.  "++" and "--" are taken to be
+  /// postfix operators, as in `b++ / c`, and the keywords set with
+  /// [LexerDef::set_operand_keywords] are taken to be operands.  Comments
+  /// take precedence, and a regular expression cannot span lines.  The
+  /// default is false.  Other rules can be written as hooks, see
+  /// [HookContext::previous].
+  pub fn set_regex_literals(&mut self, b:bool) {self.regex_literals=b;}
+  /// sets the keywords, given as a whitespace-separated string, after which
+  /// a '/' is a division rather than the start of a regular expression (see
+  /// [LexerDef::set_regex_literals]).  The default is "this super true
+  /// false null".
+  pub fn set_operand_keywords(&mut self, kws:&'t str)
+  {
+    self.operand_keywords = kws.split_whitespace().collect();
+  }
+  /// sets the numeric formats that are recognized, see [NumberFormat]
+  pub fn set_number_format(&mut self, f:NumberFormat) {self.numbers=f;}
+  /// sets whether keywords are recognized regardless of case, as in SQL.
+  /// Keywords must then be added in lowercase.  The default is false.
+  pub fn set_case_insensitive_keywords(&mut self, b:bool) {self.keywords_ignore_case=b;}
+  /// sets whether multi-line comments nest, as in Rust and Lisp, so that
+  /// `/* a /* b */ c */` is a single comment.  The default is false.
+  pub fn set_nested_comments(&mut self, b:bool) {self.nested_comments=b;}
+  /// sets the characters, in addition to ascii letters and '_', that can
+  /// start an alphanumeric sequence, and those, in addition to ascii
+  /// letters, digits and '_', that can continue it.  For example,
+  /// `set_identifier_chars("?!-", "?!->")` makes names such as `null?`,
+  /// `set-car!` and `list->vector` single [RawToken::Alphanum]s.  A '+'
+  /// or '-' among the start characters that is followed by a digit is
+  /// instead the sign of a number, so that `-4` is `Num(-4)` and `-2.5`
+  /// is `Float(-2.5)`, and a '.' followed by a digit starts a float.
+  /// Characters that are singles or start doubles are still returned as
+  /// symbols.
+  pub fn set_identifier_chars(&mut self, start:&str, cont:&str)
+  {
+    self.ident_start = start.chars().collect();
+    self.ident_continue = cont.chars().collect();
+  }
+  // length of the alphanumeric sequence at the start of s, with identifier
+  // characters other than the default
+  fn identifier_len(&self, s:&str) -> usize
+  {
+    let mut cs = s.char_indices();
+    match cs.next() {
+      Some((_,c)) if c.is_ascii_alphabetic() || c=='_' || self.ident_start.contains(&c) => {
+        if matches!(c,'+'|'-'|'.') && s[1..].starts_with(|d:char|d.is_ascii_digit()) {return 0;}
+      },
+      _ => {return 0;},
+    }
+    for (i,c) in cs {
+      if !(c.is_ascii_alphanumeric() || c=='_' || self.ident_continue.contains(&c)) {return i;}
+    }
+    s.len()
+  }
+  // length of the longest comment delimiter, hook prefix or reader macro,
+  // which are compared with the input where a token may start
+  pub(crate) fn longest_prefix(&self) -> usize
+  {
+    let hooks = self.hooks.iter().map(|h| h.prefix.len());
+    let macros = self.reader_macros.iter().map(|m| m.len());
+    hooks.chain(macros).chain([self.line_comment.len(),self.ml_comment_start.len()]).max().unwrap_or(0)
+  }
+  /// returns a hash of the rules of this definition, which differs between
+  /// definitions that tokenize some text differently, so that a cached
+  /// [TokenStream](crate::TokenStream) is not used with other rules.  The
+  /// functions of hooks cannot be compared, so hooks are identified only
+  /// by their prefixes.
+  pub fn config_hash(&self) -> u64
+  {
+    let LexerDef{doubles,singles,keywords,line_comment,ml_comment_start,ml_comment_end,
+      string_delimiters,backslash_escapes,triple_quotes,char_literals,regex_literals,
+      operand_keywords,numbers,keywords_ignore_case,nested_comments,ident_start,ident_continue,
+      reader_macros,hooks} = self;
+    let mut doubles:Vec<_> = doubles.iter().collect();
+    let mut singles:Vec<_> = singles.iter().collect();
+    let mut keywords:Vec<_> = keywords.iter().collect();
+    let mut operand_keywords:Vec<_> = operand_keywords.iter().collect();
+    let mut reader_macros:Vec<_> = reader_macros.iter().collect();
+    doubles.sort(); singles.sort(); keywords.sort(); operand_keywords.sort(); reader_macros.sort();
+    let hooks:Vec<_> = hooks.iter().map(|h| h.prefix).collect();
+    content_hash(&format!("{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}",
+      doubles,singles,keywords,line_comment,ml_comment_start,ml_comment_end,
+      string_delimiters,backslash_escapes,triple_quotes,char_literals,regex_literals,
+      operand_keywords,numbers,keywords_ignore_case,nested_comments,ident_start,ident_continue,
+      reader_macros,hooks))
+  }
+  /// creates a tokenizer that uses this definition, with the given input
+  pub fn tokenizer(&'t self, input:&'t str) -> StrTokenizer<'t>
+  {
+    let mut stk = StrTokenizer::with_def(self);
+    stk.set_input(input);
+    stk
+  }
+}//impl LexerDef
+
+/// The numeric literals recognized by a [StrTokenizer], set with
+/// [LexerDef::set_number_format].  Decimal integers and floats with a
+/// fraction, such as 2.5 or .5, are always recognized.  The default
+/// recognizes hexadecimal integers only in addition to those.  With any
+/// other format, integers that do not fit in an i64 are tokenizer errors.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub struct NumberFormat
+{
+   /// hexadecimal integers such as 0x1F
+   pub hex: bool,
+   /// binary integers such as 0b101
+   pub binary: bool,
+   /// octal integers such as 0o17
+   pub octal: bool,
+   /// floats with exponents such as 1e10 and 2.5E-3
+   pub exponents: bool,
+   /// underscores between digits, such as 1_000_000
+   pub underscores: bool,
+}
+impl Default for NumberFormat
+{
+  fn default() -> Self
+  { NumberFormat{hex:true, binary:false, octal:false, exponents:false, underscores:false} }
+}
+
+// scans a number at the start of s with a format other than the default,
+// returning the token, or an error message, and its length
+pub(crate) fn scan_number<'t>(s:&str, fmt:&NumberFormat) -> Option<(Result<RawToken<'t>,&'static str>,usize)>
+{
+  let b = s.as_bytes();
+  let digits = |from:usize, radix:u32| -> usize {
+    let mut i = from;
+    while i<b.len() && ((b[i] as char).is_digit(radix) || (fmt.underscores && b[i]==b'_' && i>from)) {i+=1;}
+    i
+  };
+  let isdigit = |i:usize| i<b.len() && b[i].is_ascii_digit();
+  if b.len()>2 && b[0]==b'0' {
+    let radix = match b[1] {
+      b'x' | b'X' if fmt.hex => 16,
+      b'b' | b'B' if fmt.binary => 2,
+      b'o' | b'O' if fmt.octal => 8,
+      _ => 0,
+    };
+    if radix>0 && (b[2] as char).is_digit(radix) {
+      let end = digits(2,radix);
+      let text:String = s[2..end].chars().filter(|c|*c!='_').collect();
+      return Some((i64::from_str_radix(&text,radix).map(Num).map_err(|_|"number too large"),end));
+    }
+  }
+  let mut end = digits(0,10);
+  let mut float = false;
+  if end==0 && !(b.first()==Some(&b'.') && isdigit(1)) {return None;}
+  if end<b.len() && b[end]==b'.' && isdigit(end+1) {
+    end = digits(end+1,10);
+    float = true;
+  }
+  if fmt.exponents && end<b.len() && (b[end]==b'e' || b[end]==b'E') {
+    let sign = if end+1<b.len() && (b[end+1]==b'+' || b[end+1]==b'-') {1} else {0};
+    if isdigit(end+1+sign) {
+      end = digits(end+1+sign,10);
+      float = true;
+    }
+  }
+  let text:String = s[..end].chars().filter(|c|*c!='_').collect();
+  if float {Some((text.parse::<f64>().map(Float).map_err(|_|"malformed number"),end))}
+  else {Some((text.parse::<i64>().map(Num).map_err(|_|"number too large"),end))}
+}
+
+// the position after the end of the nested comment starting at position
+// start of input, or None if it is unclosed
+fn nested_comment_end(input:&str, start:usize, cms:&str, cme:&str) -> Option<usize>
+{
+  let mut depth = 0;
+  let mut i = start;
+  while i<input.len() {
+    if input[i..].starts_with(cms) { depth+=1; i+=cms.len(); }
+    else if input[i..].starts_with(cme) {
+      depth-=1; i+=cme.len();
+      if depth==0 {return Some(i);}
+    }
+    else { i += input[i..].chars().next().unwrap().len_utf8(); }
+  }
+  None
+}
+
+// whether a '/' after the given token starts a regular expression, see
+// LexerDef::set_regex_literals
+fn regex_can_follow(def:&LexerDef, previous:Option<(TokenKind,&str)>) -> bool
+{
+  match previous {
+    None => true,
+    Some((TokenKind::Symbol,s)) => !matches!(s,")"|"]"|"++"|"--"),
+    Some((TokenKind::Keyword,s)) => !def.operand_keywords.contains(s),
+    Some(_) => false,
+  }
+}
+
+// the length of the regular expression literal at the start of s, which
+// starts with '/', and that of its flags.  Returns None if it is unclosed.
+fn regex_literal_len(s:&str) -> Option<(usize,usize)>
+{
+  let b = s.as_bytes();
+  let mut i = 1;
+  let mut in_class = false;
+  while i<b.len() {
+    match b[i] {
+      b'\n' | b'\r' => {return None;},
+      b'\\' => { i+=1; if b.get(i).is_some_and(|c|*c==b'\n' || *c==b'\r') {return None;} },
+      b'[' => {in_class=true;},
+      b']' => {in_class=false;},
+      b'/' if !in_class => {
+        let flags = b[i+1..].iter().take_while(|c|c.is_ascii_alphabetic()).count();
+        return Some((i+1,flags));
+      },
+      _ => {},
+    }
+    i+=1;
+  }
+  None
+}
 
 /// Generic str tokenizer that produces [RawToken]s.
 pub struct StrTokenizer<'t>
 {
-   decuint:Regex,
-   hexnum:Regex,
-   floatp:Regex,
+   decuint:&'static Regex,
+   hexnum:&'static Regex,
+   floatp:&'static Regex,
    //strlit:Regex,
-   alphan:Regex,
-   nonalph:Regex,
-   doubles:HashSet<&'t str>,   
-   singles:HashSet<char>,
+   alphan:&'static Regex,
+   nonalph:&'static Regex,
+   def: Cow<'t,LexerDef<'t>>,
    //other_syms: Vec<&'t str>,
    input: &'t str,
    position: usize,
    /// flag to toggle whether whitespaces should be returned as Whitespace tokens,
    /// default is false.
    pub keep_whitespace:bool,
-   /// flag to toggle whether newline characters ('\n') are returned as Newline
-   /// tokens. Default is false.  Note that if this flag is set to true then
+   /// flag to toggle whether line terminators ('\n', "\r\n" or '\r') are
+   /// returned as Newline tokens. Default is false.  The kind of terminator of
+   /// the last newline is returned by [StrTokenizer::line_ending].  Note that if this flag is set to true then
    /// newline characters are treated differently from other whitespaces.
    /// For example, when parsing languages like Python, both keep_whitespace
    /// and keep_newline should be set to true.  
    pub keep_newline:bool,
    line:usize,
-   line_comment:&'t str,
-   ml_comment_start:&'t str,
-   ml_comment_end:&'t str,
    /// flag to determine if comments are kept and returned as Verbatim tokens,
    /// default is false.
    pub keep_comment:bool,
    line_start:usize, // keep starting position of line, for column info
    src:&'t str,
+   src_id:usize,
+   last_error:&'static str,
+   error_span:(usize,usize),
+   quiet:bool,
+   tab_width:usize,
+   /// flag to return whitespaces as [RawToken::WhitespaceText], with their
+   /// text and visual width, instead of [RawToken::Whitespace].  It has
+   /// no effect unless keep_whitespace is also set.  Default is false.
+   pub whitespace_text:bool,
+   /// flag to return a [RawToken::LexError] for indentation (whitespaces
+   /// at the start of a line) that mixes tabs and spaces.  Default is false.
+   pub mixed_indent_error:bool,
+   /// flag to also recognize U+2028 and U+2029 as line terminators, in
+   /// addition to `\n`, `\r\n` and `\r`.  Default is false.
+   pub unicode_newlines:bool,
+   line_ending:Option<LineEnding>,
+   // tokens read ahead by peek, each with its start and the state after it
+   lookahead:VecDeque<((RawToken<'t>,usize,usize),usize,LexState)>,
+   previous:Option<(TokenKind,usize,usize)>, // kind and span of the last token that is not trivia
+   preceding:Option<(TokenKind,&'t str)>, // last token before the input, if it is part of a larger text
+   comment:bool, // whether the last token scanned is a comment
+}
+impl<'t> Default for StrTokenizer<'t>
+{
+  fn default() -> Self { StrTokenizer::new() }
 }
 impl<'t> StrTokenizer<'t>
 {
   /// creats a new tokenizer with defaults, *does not* set input.
   pub fn new() -> StrTokenizer<'t>
   {
-    let decuint = Regex::new(r"^\d+").unwrap();
-    let hexnum = Regex::new(r"^0x[\dABCDEFabcdef]+").unwrap();
-    let floatp = Regex::new(r"^\d*\x2E\d+").unwrap();
+    StrTokenizer::with_cow(Cow::Owned(LexerDef::new()))
+  }// new
+  /// creates a tokenizer that borrows the given [LexerDef], *does not*
+  /// set input.  Creating a tokenizer this way does not allocate memory.
+  pub fn with_def(def:&'t LexerDef<'t>) -> StrTokenizer<'t>
+  {
+    StrTokenizer::with_cow(Cow::Borrowed(def))
+  }
+  pub(crate) fn with_cow(def:Cow<'t,LexerDef<'t>>) -> StrTokenizer<'t>
+  {
+    // the regular expressions are compiled only once, and shared by all
+    // tokenizers
+    let decuint = DECUINT.get_or_init(|| Regex::new(r"^\d+").unwrap());
+    let hexnum = HEXNUM.get_or_init(|| Regex::new(r"^0x[\dABCDEFabcdef]+").unwrap());
+    let floatp = FLOATP.get_or_init(|| Regex::new(r"^\d*\x2E\d+").unwrap());
     //let strlit = Regex::new(r"^\x22(?s)(.*?)\x22").unwrap();
-    let alphan = Regex::new(r"^[_a-zA-Z][_\da-zA-Z]*").unwrap();
-    let nonalph=Regex::new(r"^[!@#$%\^&*\?\-\+\*/\.,<>=~`';:\|\\]+").unwrap();
-    let mut doubles = HashSet::with_capacity(16);    
-    let mut singles = HashSet::with_capacity(16);
-    for c in ['(',')','[',']','{','}'] {singles.insert(c);}
+    let alphan = ALPHAN.get_or_init(|| Regex::new(r"^[_a-zA-Z][_\da-zA-Z]*").unwrap());
+    let nonalph = NONALPH.get_or_init(|| Regex::new(r"^[!@#$%\^&*\?\-\+\*/\.,<>=~`';:\|\\]+").unwrap());
     //let mut other_syms = Vec::with_capacity(32);
     let input = "";
     let position = 0;
     let keep_whitespace=false;
     let keep_newline=false;
     let line = 1;
-    let line_comment = "//";
-    let ml_comment_start="/*";
-    let ml_comment_end="*/";    
     let keep_comment=false;
     let line_start=0;
     let src = "";
-    StrTokenizer{decuint,hexnum,floatp,/*strlit,*/alphan,nonalph,doubles,singles,input,position,keep_whitespace,keep_newline,line,line_comment,ml_comment_start,ml_comment_end,keep_comment,line_start,src}
-  }// new
+    let src_id = 0;
+    let last_error = "";
+    let error_span = (0,0);
+    let quiet = false;
+    let tab_width = 0;
+    let whitespace_text = false;
+    let mixed_indent_error = false;
+    let unicode_newlines = false;
+    let line_ending = None;
+    let lookahead = VecDeque::new();
+    let previous = None;
+    let preceding = None;
+    let comment = false;
+    StrTokenizer{decuint,hexnum,floatp,/*strlit,*/alphan,nonalph,def,input,position,keep_whitespace,keep_newline,line,keep_comment,line_start,src,src_id,last_error,error_span,quiet,tab_width,whitespace_text,mixed_indent_error,unicode_newlines,line_ending,lookahead,previous,preceding,comment}
+  }
+  /// returns the [LexerDef] of this tokenizer
+  pub fn get_def(&self) -> &LexerDef<'t> {&self.def}
+  // the definition of this tokenizer, copied first if it is borrowed
+  pub(crate) fn def_mut(&mut self) -> &mut LexerDef<'t> {self.def.to_mut()}
   /// adds a symbol of exactly length two. If the length is not two the function
   /// has no effect.  Note that these symbols override all other types except for
   /// leading whitespaces and comments markers, e.g. "//" will have precedence
   /// over "/" and "==" will have precedence over "=".
-  pub fn add_double(&mut self, s:&'t str)
-  {
-    if s.len()==2 { self.doubles.insert(s); }
-  }
+  pub fn add_double(&mut self, s:&'t str) { self.def.to_mut().add_double(s); }
   /// add a single-character symbol.  The type of the symbol overrides other
   /// types except for whitespaces, comments and double-character symbols.
-  pub fn add_single(&mut self, c:char) { self.singles.insert(c);}
+  pub fn add_single(&mut self, c:char) { self.def.to_mut().add_single(c); }
+  /// adds keywords, given as a whitespace-separated string such as
+  /// "if else while".  Alphanumeric sequences that are keywords are
+  /// returned as [RawToken::Keyword] instead of [RawToken::Alphanum].
+  pub fn add_keywords(&mut self, kws:&'t str) { self.def.to_mut().add_keywords(kws); }
+  /// adds reader macros, given as a whitespace-separated string such as
+  /// "' ` , ,@".  See [LexerDef::add_reader_macros].
+  pub fn add_reader_macros(&mut self, ms:&'t str) { self.def.to_mut().add_reader_macros(ms); }
   /*
   /// add symbol of length greater than two. Symbols that are prefixes of
   /// other symbols should be added after the longer symbols.
@@ -128,151 +902,540 @@ impl<'t> StrTokenizer<'t>
   pub fn set_input(&mut self, inp:&'t str)
   {
     self.input=inp.trim_end(); self.position=0; self.line=1; self.line_start=0;
+    self.last_error=""; self.line_ending=None;
+    self.lookahead.clear();
+    self.previous=None; self.preceding=None;
+  }
+  // sets input without trimming trailing whitespaces
+  pub(crate) fn set_untrimmed_input(&mut self, inp:&'t str)
+  {
+    self.input=inp; self.position=0; self.line=1; self.line_start=0;
+    self.last_error=""; self.line_ending=None;
+    self.lookahead.clear();
+    self.previous=None; self.preceding=None;
+  }
+  // records an error and the byte positions of its cause, and prints it to
+  // stderr, unless quiet
+  fn report_error(&mut self, msg:&'static str, start:usize, end:usize, line:usize, column:usize)
+  {
+    self.last_error = msg;
+    self.error_span = (start,end);
+    if !self.quiet {
+      // column is the byte column of start, reported as the token would be
+      let column = if self.tab_width==0 {column} else {self.visual_column(start+1-column,start)+1};
+      eprintln!("Tokenizer error: {} starting on line {}, column {}",msg,line,column);
+    }
   }
+  // description of the most recent error, such as "unclosed string"
+  pub(crate) fn last_error(&self) -> &'static str {self.last_error}
+  /// returns a [Diagnostic] for the most recent [RawToken::LexError]
+  /// returned by the tokenizer since the input was set, if any.  The span
+  /// of the diagnostic covers the start of the offending construct, such as
+  /// the opening quote of an unclosed string.  It can be rendered with
+  /// [Diagnostic::render_for] given the tokenizer, or with
+  /// [Diagnostic::emit] given the [LexSource] of the tokenizer.
+  pub fn error_diagnostic(&self) -> Option<Diagnostic>
+  {
+    if self.last_error.is_empty() {return None;}
+    Some(Diagnostic::error(self.last_error,self.error_span.0,self.error_span.1))
+  }
+  /// suppresses the printing of errors to stderr, for instance when they
+  /// are reported with [StrTokenizer::error_diagnostic] instead.  The
+  /// default is false.
+  pub fn set_quiet(&mut self, q:bool) {self.quiet=q;}
+  /// returns a hash of the rules and settings of the tokenizer: the
+  /// [LexerDef::config_hash] of its definition, combined with its flags
+  /// and tab width, which also determine the tokens it returns
+  pub fn config_hash(&self) -> u64
+  {
+    content_hash(&format!("{}|{}|{}|{}|{}|{}|{}|{}",self.def.config_hash(),
+      self.keep_whitespace,self.keep_newline,self.keep_comment,self.whitespace_text,
+      self.mixed_indent_error,self.unicode_newlines,self.tab_width))
+  }
+  // byte position at which the current line starts
+  pub(crate) fn line_start(&self) -> usize {self.line_start}
   /// sets the symbol that begins a single-line comment. The default is
   /// "//".  If this is set to the empty string then no line-comments are
   /// recognized.
-  pub fn set_line_comment(&mut self,cm:&'t str) {
-    self.line_comment=cm;
-  }
+  pub fn set_line_comment(&mut self,cm:&'t str) { self.def.to_mut().set_line_comment(cm); }
   /// sets the symbols used to delineate multi-line comments using a
   /// whitespace separated string such as "/* */".  These symbols are
   /// also the default.  Set this to the empty string to disable
   /// multi-line comments.
-  pub fn set_multiline_comments(&mut self,cm:&'t str)
-  {
-    if cm.len()==0 {
-      self.ml_comment_start=""; self.ml_comment_end=""; return;
-    }
-    let split:Vec<_> = cm.split_whitespace().collect();
-    if split.len()!=2 {return;}
-    self.ml_comment_start = split[0].trim();
-    self.ml_comment_end = split[1].trim();
-  }
+  pub fn set_multiline_comments(&mut self,cm:&'t str) { self.def.to_mut().set_multiline_comments(cm); }
+  /// sets the start and end symbols of multi-line comments separately.
+  /// The function has no effect if either symbol is empty.
+  pub fn set_multiline_comment_delimiters(&mut self, start:&'t str, end:&'t str)
+  { self.def.to_mut().set_multiline_comment_delimiters(start,end); }
+  /// sets the characters that delimit string literals, see
+  /// [LexerDef::set_string_delimiters]
+  pub fn set_string_delimiters(&mut self, delims:&str) { self.def.to_mut().set_string_delimiters(delims); }
+  /// sets whether a backslash escapes the next character in string
+  /// literals, see [LexerDef::set_backslash_escapes]
+  pub fn set_backslash_escapes(&mut self, b:bool) { self.def.to_mut().set_backslash_escapes(b); }
+  /// sets whether strings can be delimited by three quotes, see
+  /// [LexerDef::set_triple_quotes]
+  pub fn set_triple_quotes(&mut self, b:bool) { self.def.to_mut().set_triple_quotes(b); }
+  /// sets whether char literals are recognized, see
+  /// [LexerDef::set_char_literals]
+  pub fn set_char_literals(&mut self, b:bool) { self.def.to_mut().set_char_literals(b); }
+  /// sets whether regular expression literals are recognized, see
+  /// [LexerDef::set_regex_literals]
+  pub fn set_regex_literals(&mut self, b:bool) { self.def.to_mut().set_regex_literals(b); }
+  /// sets the keywords after which a '/' is a division, see
+  /// [LexerDef::set_operand_keywords]
+  pub fn set_operand_keywords(&mut self, kws:&'t str) { self.def.to_mut().set_operand_keywords(kws); }
+  /// sets the numeric formats that are recognized, see [NumberFormat]
+  pub fn set_number_format(&mut self, f:NumberFormat) { self.def.to_mut().set_number_format(f); }
+  /// sets whether keywords are recognized regardless of case, see
+  /// [LexerDef::set_case_insensitive_keywords]
+  pub fn set_case_insensitive_keywords(&mut self, b:bool) { self.def.to_mut().set_case_insensitive_keywords(b); }
+  /// sets whether multi-line comments nest, see [LexerDef::set_nested_comments]
+  pub fn set_nested_comments(&mut self, b:bool) { self.def.to_mut().set_nested_comments(b); }
+  /// sets the characters that can start and continue alphanumeric
+  /// sequences, see [LexerDef::set_identifier_chars]
+  pub fn set_identifier_chars(&mut self, start:&str, cont:&str) { self.def.to_mut().set_identifier_chars(start,cont); }
   /// the current line that the tokenizer is on
   pub fn line(&self)->usize {self.line}
   /// the current column of the tokenizer
-  pub fn column(&self)->usize {self.position-self.line_start+1}
+  pub fn column(&self)->usize {self.visual_column(self.line_start,self.position)+1}
+  // column of the current position in bytes, starting at 1
+  fn byte_column(&self)->usize {self.position-self.line_start+1}
+  /// sets the width of tab stops.  If the width is not zero, the column
+  /// numbers of tokens are visual columns, counted in characters with
+  /// each tab advancing to the next tab stop, as in an editor, and the
+  /// width of [RawToken::WhitespaceText] is computed likewise.  The default
+  /// is zero, in which case columns are counted in bytes.
+  pub fn set_tab_width(&mut self, width:usize) {self.tab_width=width;}
+  /// returns the width of tab stops, see [StrTokenizer::set_tab_width]
+  pub fn tab_width(&self) -> usize {self.tab_width}
+  // zero-based column of byte position pos of the line starting at linestart
+  fn visual_column(&self, linestart:usize, pos:usize) -> usize
+  {
+    if self.tab_width==0 {return pos-linestart;}
+    let tw = self.tab_width;
+    self.input[linestart..pos].chars().fold(0, |col,c| if c=='\t' {(col/tw+1)*tw} else {col+1})
+  }
+  // byte position of the start of the given line, knowing that it is
+  // between line0, which starts at lstart0, and the current line
+  fn start_of_line(&self, line:usize, line0:usize, lstart0:usize) -> usize
+  {
+    if line==self.line {return self.line_start;}
+    let mut ls = lstart0;
+    for _ in line0..line {
+      if let Some((i,_,n)) = find_line_break(self.input,ls,self.unicode_newlines) {ls=i+n;}
+    }
+    ls
+  }
   /// returns the current absolute byte position of the Tokenizer
   pub fn position(&self)-> usize {self.position}
   /// returns the source of the tokenizer such as URL or filename
   pub fn get_source(&self) -> &str {self.src}
   pub fn set_source<'u:'t>(&mut self, s:&'u str) {self.src=s;}
+  /// returns the numerical id of the source of the tokenizer, which is
+  /// the id of the [LexSource] it was created from, or zero
+  pub fn get_source_id(&self) -> usize {self.src_id}
+  /// sets the numerical id of the source of the tokenizer
+  pub fn set_source_id(&mut self, id:usize) {self.src_id=id;}
+  /// returns the entire input str of the tokenizer
+  pub fn get_input(&self) -> &'t str {self.input}
+  /// returns the line of input that the tokenizer is currently on,
+  /// without the terminating newline
+  pub fn current_line(&self) -> &'t str
+  {
+    let startl = if self.line_start>self.input.len() {self.input.len()} else {self.line_start};
+    let endl = find_line_break(self.input,startl,self.unicode_newlines).map(|(i,_,_)|i).unwrap_or(self.input.len());
+    &self.input[startl..endl]
+  }
+  /// saves the complete state of the tokenizer between tokens as a
+  /// [LexState], so that it can go back to it with [StrTokenizer::restore],
+  /// as needed by backtracking parsers.
+  ///
+  /// Example:
+  ///```ignore
+  ///  let saved = tokenizer.checkpoint();
+  ///  if !parse_alternative(&mut tokenizer) {
+  ///    tokenizer.restore(saved);   // try another alternative
+  ///    parse_other_alternative(&mut tokenizer);
+  ///  }
+  ///```
+  pub fn checkpoint(&self) -> LexState
+  {
+    LexState {
+      position:self.position, line:self.line, line_start:self.line_start,
+      line_ending:self.line_ending, last_error:self.last_error, error_span:self.error_span,
+      previous:self.previous,
+    }
+  }
+  /// returns the tokenizer to a [LexState] saved by [StrTokenizer::checkpoint]
+  /// on the same input.  The state is not checked.  Tokens read ahead by
+  /// [StrTokenizer::peek] are discarded.
+  pub fn restore(&mut self, state:LexState)
+  {
+    self.lookahead.clear();
+    self.set_state(state);
+  }
+  fn set_state(&mut self, state:LexState)
+  {
+    self.position = state.position; self.line = state.line; self.line_start = state.line_start;
+    self.line_ending = state.line_ending;
+    self.last_error = state.last_error; self.error_span = state.error_span;
+    self.previous = state.previous;
+  }
+  /// returns the kind and text of the last token returned that is not
+  /// trivia (see [RawToken::is_trivia]), or None at the start of the input.
+  /// It decides whether a '/' starts a [RawToken::RegexLit], and is passed
+  /// to hooks by [HookContext::previous].  Tokens read ahead by
+  /// [StrTokenizer::peek] are not included.
+  pub fn previous(&self) -> Option<(TokenKind,&'t str)>
+  {
+    match self.previous {
+      Some((kind,start,end)) => Some((kind,self.get_slice(start,end))),
+      None => self.preceding,
+    }
+  }
+  // sets the token taken to precede the input, when the input is a window
+  // of a larger text
+  pub(crate) fn set_preceding(&mut self, p:Option<(TokenKind,&'t str)>) {self.preceding=p;}
+  // whether the last token scanned is a comment, rather than a Verbatim
+  // token returned by a hook
+  pub(crate) fn is_comment(&self) -> bool {self.comment}
+  // returns the next token with its line and byte column, the byte position
+  // at which it starts and the position of the start of its line.  Tokens
+  // read ahead are discarded, since the state is that after the last token
+  // consumed.
+  pub(crate) fn next_token_at(&mut self) -> Option<(RawToken<'t>,usize,usize,usize,usize)>
+  {
+    self.lookahead.clear();
+    self.scan_token_at()
+  }
+  fn scan_token_at(&mut self) -> Option<(RawToken<'t>,usize,usize,usize,usize)>
+  {
+    let (line0,lstart0) = (self.line,self.line_start);
+    self.comment = false;
+    let (token,line,column) = self.scan_token()?;
+    let ls = self.start_of_line(line,line0,lstart0);
+    // whitespaces that span lines start before the line they are reported on
+    let start = match token {
+      Whitespace(n) => self.position-n,
+      WhitespaceText(w,_) => self.position-w.len(),
+      _ => ls+column-1,
+    };
+    if !token.is_trivia() { self.previous = Some((token.kind(),start,self.position)); }
+    Some((token,line,column,start,ls))
+  }
+  /// returns the kind of the most recent line terminator passed by the
+  /// tokenizer, such as that of the last [RawToken::Newline] returned
+  pub fn line_ending(&self) -> Option<LineEnding> {self.line_ending}
+  // counts the line terminators between the given positions
+  fn count_lines(&mut self, from:usize, to:usize)
+  {
+    let mut ci = from;
+    while let Some((i,e,n)) = find_line_break(&self.input[..to],ci,self.unicode_newlines) {
+      self.line+=1; ci=i+n; self.line_start=ci; self.line_ending=Some(e);
+    }
+  }
+  /// returns the slice of input between the given byte positions, or the
+  /// empty string if the positions are invalid
+  pub fn get_slice(&self, start:usize, end:usize) -> &'t str
+  {
+    if start<=end && end<=self.input.len() {self.input.get(start..end).unwrap_or("")} else {""}
+  }
   
   /// returns next token, along with starting line and column numbers.
   /// This function will return None at end of stream or LexError along
   /// with a message printed to stderr if a tokenizer error occured.
   pub fn next_token(&mut self) -> Option<(RawToken<'t>,usize,usize)>
+  {
+    if let Some((token,_,after)) = self.lookahead.pop_front() {
+      self.set_state(after);
+      return Some(token);
+    }
+    let (token,line,column,_,ls) = self.scan_token_at()?;
+    if self.tab_width==0 {return Some((token,line,column));}
+    let column = self.visual_column(ls,(ls+column-1).min(self.input.len()))+1;
+    Some((token,line,column))
+  }//next_token
+
+  // fills the lookahead buffer with up to k+1 tokens, leaving the state of
+  // the tokenizer unchanged
+  fn fill_lookahead(&mut self, k:usize)
+  {
+    if self.lookahead.len()>k {return;}
+    let current = self.checkpoint();
+    if let Some((_,_,after)) = self.lookahead.back() { let after=*after; self.set_state(after); }
+    while self.lookahead.len()<=k {
+      let (token,line,column,start,ls) = match self.scan_token_at() {
+        Some(t) => t,
+        None => break,
+      };
+      let column = if self.tab_width==0 {column}
+        else {self.visual_column(ls,(ls+column-1).min(self.input.len()))+1};
+      let after = self.checkpoint();
+      self.lookahead.push_back(((token,line,column),start,after));
+    }
+    self.set_state(current);
+  }
+
+  /// returns the token k positions ahead, with its line and column
+  /// numbers, without consuming any token: `peek(0)` is the token that
+  /// will be returned next.  Returns None if there are not that many
+  /// tokens left.  The position, line and column of the tokenizer are
+  /// those after the last token consumed, regardless of how far it has
+  /// peeked.  Tokenizer errors are reported when they are first peeked.
+  ///
+  /// Example:
+  ///```ignore
+  ///  // distinguish a labeled statement from an expression, as in "x : ..."
+  ///  if tokenizer.peek_kind(0)==Some(TokenKind::Alphanum)
+  ///     && matches!(tokenizer.peek(1), Some((RawToken::Symbol(":"),_,_))) {
+  ///    let label = tokenizer.advance();
+  ///    tokenizer.expect_symbol(":")?;
+  ///  }
+  ///```
+  pub fn peek(&mut self, k:usize) -> Option<&(RawToken<'t>,usize,usize)>
+  {
+    self.fill_lookahead(k);
+    self.lookahead.get(k).map(|(t,_,_)| t)
+  }
+  /// returns the kind of the token k positions ahead, see [StrTokenizer::peek]
+  pub fn peek_kind(&mut self, k:usize) -> Option<TokenKind>
+  {
+    self.peek(k).map(|(t,_,_)| t.kind())
+  }
+  /// consumes and returns the next token, as [StrTokenizer::next_token]
+  pub fn advance(&mut self) -> Option<(RawToken<'t>,usize,usize)>
+  {
+    self.next_token()
+  }
+  /// consumes and returns the next token if it is of the given kind.
+  /// Otherwise, the token is not consumed, and a [Diagnostic] about it
+  /// is returned.
+  pub fn expect(&mut self, kind:TokenKind) -> Result<(RawToken<'t>,usize,usize),Diagnostic>
+  {
+    self.expect_with(|t| t.kind()==kind, &format!("{:?}",kind))
+  }
+  /// consumes and returns the next token if it is the [RawToken::Symbol]
+  /// sym.  Otherwise, the token is not consumed, and a [Diagnostic] about
+  /// it is returned.
+  pub fn expect_symbol(&mut self, sym:&str) -> Result<(RawToken<'t>,usize,usize),Diagnostic>
+  {
+    self.expect_with(|t| matches!(t, Symbol(s) if *s==sym), &format!("\"{}\"",sym))
+  }
+  fn expect_with<F:Fn(&RawToken<'t>)->bool>(&mut self, accept:F, expected:&str) -> Result<(RawToken<'t>,usize,usize),Diagnostic>
+  {
+    self.fill_lookahead(0);
+    match self.lookahead.front() {
+      Some(((t,_,_),_,_)) if accept(t) => Ok(self.next_token().unwrap()),
+      Some(((t,_,_),start,after)) => {
+        // the error of a token read ahead is in the state after it
+        let found = match t { LexError => after.last_error, _ => self.get_slice(*start,after.position) };
+        Err(Diagnostic::error(format!("expected {}, found {:?}",expected,found),*start,after.position))
+      },
+      None => {
+        let end = self.input.len();
+        Err(Diagnostic::error(format!("expected {}, found end of input",expected),end,end))
+      },
+    }
+  }
+
+  // returns the next token with byte-based column numbers
+  fn scan_token(&mut self) -> Option<(RawToken<'t>,usize,usize)>
   {
    let mut pi = 0;
-   let clen = self.line_comment.len();
-   let (cms,cme) = (self.ml_comment_start,self.ml_comment_end);
+   let clen = self.def.line_comment.len();
+   let (cms,cme) = (self.def.ml_comment_start,self.def.ml_comment_end);
    while self.position<self.input.len()
    {
     pi = self.position;
     //if pi>=self.input.len() {return None;}
-    let mut column0 = self.column();
+    let mut column0 = self.byte_column();
     let mut line0 = self.line;
     let mut lstart0 = self.line_start;
     
     // skip/keep whitespaces
-    let mut nextchars = self.input[pi..].chars();
-    let mut c = nextchars.next().unwrap();
+    let c = self.input[pi..].chars().next().unwrap();
     //println!("NEXTCHAR is ({}), position {}",c,self.position);
     let mut i = pi;
-    while c.is_whitespace() && i < self.input.len() 
+    while let Some(wc) = self.input[i..].chars().next().filter(|wc|wc.is_whitespace())
     {
-       if c=='\n' {
-         self.line+=1; lstart0=self.line_start; self.line_start=i+1; line0=self.line;
-         if self.keep_newline { self.position = i+1; return Some((Newline,self.line-1,pi-lstart0+1)); }
+       if let Some((ending,n)) = line_break_at(self.input,i,self.unicode_newlines) {
+         self.line+=1; lstart0=self.line_start; self.line_start=i+n; line0=self.line;
+         self.line_ending = Some(ending);
+         if self.keep_newline { self.position = i+n; return Some((Newline,self.line-1,pi-lstart0+1)); }
+         i+=n; continue;
        }
-       i+= 1; 
-       if i<self.input.len() {c = nextchars.next().unwrap();}
+       i+= wc.len_utf8();
     }
     self.position = i;
+    if self.mixed_indent_error && i>pi && self.line_start>=pi && i<self.input.len() {
+      let indent = &self.input[self.line_start..i];
+      if indent.contains(' ') && indent.contains('\t') {
+        let ls = self.line_start;
+        self.report_error("mixed tabs and spaces in indentation",ls,i,self.line,1);
+        return Some((LexError,self.line,1));
+      }
+    }
+    if (i>pi && self.keep_whitespace && self.whitespace_text) {
+      // the width of whitespaces that span lines is that of the last line
+      let from = pi.max(self.line_start);
+      let width = self.visual_column(self.line_start,i)-self.visual_column(self.line_start,from);
+      return Some((WhitespaceText(&self.input[pi..i],width),line0,from-self.line_start+1));}
     if (i>pi && self.keep_whitespace) {
-      return Some((Whitespace(i-pi),line0,self.column()-(i-pi)));}
+      return Some((Whitespace(i-pi),line0,pi.max(self.line_start)-self.line_start+1));}
     else if i>pi {continue;}
     //if pi>=self.input.len() {return None;}
 
+    // hand-written scanners registered as hooks
+    if !self.def.hooks.is_empty() {
+      let column = pi-self.line_start+1;
+      let cx = HookContext::new(self.input,pi,self.line,column,self.previous());
+      if let Some((token,len)) = run_hooks(&self.def.hooks,&cx) {
+        let end = pi+len;
+        if end>self.input.len() || !self.input.is_char_boundary(end) {
+          self.position = self.input.len();
+          self.report_error("invalid length returned by hook",pi,pi+c.len_utf8(),line0,column);
+          self.count_lines(pi,self.position);
+          return Some((LexError,line0,column));
+        }
+        self.position = end;
+        if let LexError = token {
+          self.report_error("invalid token",pi,end,line0,column);
+        }
+        self.count_lines(pi,end);
+        return Some((token,line0,column));
+      }
+    }//hooks
+
     // look for line comment
-    if clen>0 && pi+clen<=self.input.len() && self.line_comment==&self.input[pi..pi+clen] {
-      if let Some(nlpos) = self.input[pi+clen..].find("\n") {
+    if clen>0 && self.input[pi..].starts_with(self.def.line_comment) {
+      if let Some((nlpos,_,_)) = find_line_break(&self.input[pi+clen..],0,self.unicode_newlines) {
         self.position = nlpos+pi+clen;
         if self.keep_comment {
+          self.comment = true;
           return Some((Verbatim(&self.input[pi..pi+clen+nlpos]),self.line,pi-self.line_start+1));
         }
         else {continue;}
       } else { // no newline fould
         self.position = self.input.len(); 
-        if self.keep_comment {return Some((Verbatim(&self.input[pi..]),self.line,pi-self.line_start+1));}
+        if self.keep_comment {
+          self.comment = true;
+          return Some((Verbatim(&self.input[pi..]),self.line,pi-self.line_start+1));
+        }
         else {break;}
       }
     }// line comment
 
     // look for multi-line comment (similar to string literals)
-    if cms.len()>0 && pi+cms.len()<=self.input.len() && &self.input[pi..pi+cms.len()] == cms {
-       if let Some(endpos) = self.input[pi+cms.len()..].find(cme) {
-         self.position = pi+cms.len()+endpos+cme.len();
+    if !cms.is_empty() && self.input[pi..].starts_with(cms) {
+       let end = if self.def.nested_comments {nested_comment_end(self.input,pi,cms,cme)}
+         else {self.input[pi+cms.len()..].find(cme).map(|endpos| pi+cms.len()+endpos+cme.len())};
+       if let Some(end) = end {
+         self.position = end;
        } else {
          self.position = self.input.len();
-         eprintln!("Tokenizer error: unclosed multi-line comment starting on line {}, column {}",line0,pi-self.line_start+1);
-         return Some((LexError,line0,pi-self.line_start+1));
+         let column = pi-self.line_start+1;
+         self.report_error("unclosed multi-line comment",pi,pi+cms.len(),line0,column);
+         self.count_lines(pi,self.position);
+         return Some((LexError,line0,column));
        }
        // find newline chars
-       let mut ci = pi;
-       while let Some(nli) = self.input[ci..self.position].find('\n')
-       {
-          self.line+=1; ci += nli+1;  self.line_start=ci;
-          // Newline token is never returned if inside string literal
-       }
+       self.count_lines(pi,self.position);
        if self.keep_comment {
+         self.comment = true;
          return Some((Verbatim(&self.input[pi..self.position]),line0,pi-lstart0+1));
        }
        else {continue;}
     }//multi-line comments
 
+    // look for regular expression literal where an operand can start
+    if c=='/' && self.def.regex_literals && regex_can_follow(&self.def,self.previous()) {
+      let column = pi-self.line_start+1;
+      match regex_literal_len(&self.input[pi..]) {
+        Some((len,flags)) if len>2 => {
+          self.position = pi+len+flags;
+          return Some((RegexLit(&self.input[pi+1..pi+len-1],&self.input[pi+len..self.position]),self.line,column));
+        },
+        Some(_) => {}, // empty, as in "//" without line comments
+        None => {
+          let end = find_line_break(self.input,pi,self.unicode_newlines).map(|(i,_,_)|i).unwrap_or(self.input.len());
+          self.position = end;
+          self.report_error("unclosed regular expression",pi,pi+1,self.line,column);
+          return Some((LexError,self.line,column));
+        },
+      }
+    }//regex literals
+
+
+    // look for reader macros, the longest first
+    if !self.def.reader_macros.is_empty() {
+      let rest = &self.input[pi..];
+      if let Some(len) = self.def.reader_macros.iter().filter(|m| rest.starts_with(**m)).map(|m|m.len()).max() {
+        self.position = pi+len;
+        return Some((ReaderMacro(&rest[..len]),self.line,pi-self.line_start+1));
+      }
+    }
 
     // look for doubles
-    if pi+1<self.input.len() && self.doubles.contains(&self.input[pi..pi+2]) {
+    if self.input.get(pi..pi+2).is_some_and(|d|self.def.doubles.contains(d)) {
       self.position = pi+2;
-      return Some((Symbol(&self.input[pi..pi+2]),self.line,self.column()-2));
+      return Some((Symbol(&self.input[pi..pi+2]),self.line,self.byte_column()-2));
     }
 
     // look for singles:
     //c=self.input[pi..pi+1].chars().next().unwrap();
-    if self.singles.contains(&c) {
+    if self.def.singles.contains(&c) {
      // println!("ADDING SINGLE {}",c);
-      self.position=pi+1;
-      return Some((Symbol(&self.input[pi..pi+1]),self.line,self.column()-1));
+      self.position=pi+c.len_utf8();
+      return Some((Symbol(&self.input[pi..self.position]),self.line,pi-self.line_start+1));
     }
 
     // look for char literal
-    if c=='\'' && pi+2<self.input.len() && &self.input[pi+2..pi+3]=="\'" {
-      self.position = pi+3;
-      let thechar = self.input[pi+1..pi+2].chars().next().unwrap();
-      return Some((Char(thechar),self.line,self.column()-3));
+    if c=='\'' && self.def.char_literals {
+      let mut cs = self.input[pi+1..].chars();
+      let (mut first,mut second) = (cs.next(),cs.next());
+      let mut len = first.map(|f|f.len_utf8()+2).unwrap_or(0);
+      if first==Some('\\') {
+        let escaped = match second {
+          Some('n') => Some('\n'), Some('t') => Some('\t'), Some('r') => Some('\r'),
+          Some('0') => Some('\0'), Some(e) if e=='\\' || e=='\'' || e=='"' => Some(e),
+          _ => None,
+        };
+        if escaped.is_some() { first = escaped; second = cs.next(); len = 4; }
+      }
+      if let (Some(thechar),Some('\'')) = (first,second) {
+        self.position = pi+len;
+        let (line,column) = (self.line,pi-self.line_start+1);
+        self.count_lines(pi,self.position);
+        return Some((Char(thechar),line,column));
+      }
     }
 
     // look for string literal, keep track of newlines
-    if c=='\"' {
-      let mut ci = pi+1;
+    if self.def.string_delimiters.contains(&c) {
+      let q = c as u8;
+      let bytes = self.input.as_bytes();
+      let triple = self.def.triple_quotes && bytes.get(pi..pi+3)==Some(&[q,q,q][..]);
+      let mut ci = if triple {pi+3} else {pi+1};
       while ci<self.input.len()
       {
-         if &self.input[ci..ci+1]=="\"" {
-            self.position = ci+1;
+         let b = bytes[ci];
+         if b==q && triple && bytes.get(ci..ci+3)!=Some(&[q,q,q][..]) {}
+         else if b==q && !triple && !self.def.backslash_escapes && bytes.get(ci+1)==Some(&q) {ci+=1;} // doubled delimiter
+         else if b==q {
+            self.position = if triple {ci+3} else {ci+1};
+            // Newline token is never returned if inside string literal
+            self.count_lines(pi,self.position);
             return Some((Strlit(&self.input[pi..self.position]),line0,pi-lstart0+1));
          }
-         else if &self.input[ci..ci+1] == "\n" {
-           self.line+=1; self.line_start=ci+1;
-         }
          // else need to try again!
-         else if &self.input[ci..ci+1] == "\\" {ci+=1}; // extra skip
+         else if b==b'\\' && self.def.backslash_escapes {ci+=1}; // extra skip
          ci+=1;
       }// while ci < input.len()
       // terminated without finding end of string
       self.position = self.input.len();
-        eprintln!("Tokenizer error: unclosed string starting on line {}, column {}",line0,pi-self.line_start+1);
+      self.count_lines(pi,self.position);
+        self.report_error("unclosed string",pi,pi+1,line0,pi-lstart0+1);
         return Some((LexError,line0,pi-lstart0+1)); 
     }//strlit
     /*
@@ -289,15 +1452,56 @@ impl<'t> StrTokenizer<'t>
     }//string lits are matched first, so other's aren't part of strings
     */
     
+    // a sign that can start an alphanumeric sequence is that of a number
+    // if a digit follows it, as in Lisp
+    if (c=='+' || c=='-') && self.def.ident_start.contains(&c)
+       && self.input[pi+1..].starts_with(|d:char| d.is_ascii_digit()) {
+      if let Some((num,len)) = scan_number(&self.input[pi+1..],&self.def.numbers) {
+        self.position = pi+1+len;
+        let column = pi-self.line_start+1;
+        match num {
+          Ok(Num(n)) if c=='-' => { return Some((Num(-n),self.line,column)); },
+          Ok(Float(f)) if c=='-' => { return Some((Float(-f),self.line,column)); },
+          Ok(token) => { return Some((token,self.line,column)); },
+          Err(msg) => {
+            self.report_error(msg,pi,self.position,self.line,column);
+            return Some((LexError,self.line,column));
+          },
+        }
+      }
+    }
+    // look for numbers in formats other than the default
+    if self.def.numbers!=NumberFormat::default() {
+      if let Some((num,len)) = scan_number(&self.input[pi..],&self.def.numbers) {
+        self.position = pi+len;
+        let column = pi-self.line_start+1;
+        match num {
+          Ok(token) => { return Some((token,self.line,column)); },
+          Err(msg) => {
+            self.report_error(msg,pi,pi+len,self.line,column);
+            return Some((LexError,self.line,column));
+          },
+        }
+      }
+    }
     // look for hex
-    if let Some(mat) = self.hexnum.find(&self.input[pi..]) {
+    else if let Some(mat) = self.hexnum.find(&self.input[pi..]) {
         self.position = mat.end()+pi;
-        return Some((Num(i64::from_str_radix(&self.input[pi+2..self.position],16).unwrap()),self.line,pi+3-self.line_start));        
+        return Some((Num(i64::from_str_radix(&self.input[pi+2..self.position],16).unwrap()),self.line,pi-self.line_start+1));        
     }//hexnum
-    // look for alphanum    
-    if let Some(mat) = self.alphan.find(&self.input[pi..]) {
-        self.position = mat.end()+pi;  
-        return Some((Alphanum(&self.input[pi..self.position]),self.line,pi-self.line_start+1));
+    // look for alphanum
+    let idlen = if self.def.ident_start.is_empty() && self.def.ident_continue.is_empty() {
+        self.alphan.find(&self.input[pi..]).map(|mat|mat.end()).unwrap_or(0)
+      } else {self.def.identifier_len(&self.input[pi..])};
+    if idlen>0 {
+        self.position = pi+idlen;
+        let word = &self.input[pi..self.position];
+        let keyword = if self.def.keywords_ignore_case {self.def.keywords.contains(word.to_ascii_lowercase().as_str())}
+                      else {self.def.keywords.contains(word)};
+        if keyword {
+          return Some((Keyword(word),self.line,pi-self.line_start+1));
+        }
+        return Some((Alphanum(word),self.line,pi-self.line_start+1));
     }//alphanums
 
     // floats
@@ -311,9 +1515,9 @@ impl<'t> StrTokenizer<'t>
         return Some((Num(self.input[pi..self.position].parse::<i64>().unwrap()),self.line,pi-self.line_start+1));
     }//decuint
     //check for unclosed string
-    if pi<self.input.len() && &self.input[pi..pi+1]=="\"" {
+    if pi<self.input.len() && self.input.as_bytes()[pi]==b'"' {
         self.position = self.input.len();
-        eprintln!("Tokenizer error: unclosed string starting on line {}, column {}",line0,pi-self.line_start+1);
+        self.report_error("unclosed string",pi,pi+1,line0,pi-self.line_start+1);
         return Some((LexError,line0,pi-self.line_start+1));        
     }//unclosed string
       
@@ -326,13 +1530,15 @@ impl<'t> StrTokenizer<'t>
     // at this point, must be error
     self.position = self.input.len();
     if pi<self.position {
-      eprintln!("Tokenizer error: unrecognized symbols starting on line {}, column {}",line0,pi-self.line_start+1);
-     return Some((LexError,line0,pi-self.line_start+1));
+      let column = pi-self.line_start+1;
+      self.report_error("unrecognized symbols",pi,pi+c.len_utf8(),line0,column);
+      self.count_lines(pi,self.position);
+      return Some((LexError,line0,column));
     }
     //else { return None; }
    } //while
-   return None;
-  }//next_token
+   None
+  }//scan_token
   
 }//impl StrTokenizer
 
@@ -341,48 +1547,256 @@ impl<'t> Iterator for StrTokenizer<'t>
   type Item = (RawToken<'t>,usize,usize);
   fn next(&mut self) -> Option<(RawToken<'t>,usize,usize)>
   {
-     if let Some(tok) = self.next_token() {Some(tok)} else {None}
+     self.next_token()
   }
 }//Iterator
 
 
+// storage of the contents of a LexSource
+enum SourceText
+{
+   Owned(String),
+   #[cfg(feature = "mmap")]
+   Mapped(memmap2::Mmap,usize), // validated as utf8 after the given offset
+}
+
+/// Character encodings of sources that [LexSource] can decode.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub enum Encoding
+{
+  /// UTF-8, the default
+  Utf8,
+  /// UTF-16, little-endian
+  Utf16Le,
+  /// UTF-16, big-endian
+  Utf16Be,
+  /// ISO-8859-1, where each byte is the character with the same code
+  Latin1,
+}
+
+fn invalid_data<E>(e:E) -> std::io::Error
+where E:Into<Box<dyn std::error::Error+Send+Sync>>
+{
+  std::io::Error::new(std::io::ErrorKind::InvalidData,e)
+}
+
+// detects the encoding of bytes from a byte order mark, returning the
+// encoding and the length of the mark.  Without a mark, bytes are taken to
+// be UTF-8.
+fn detect_encoding(bytes:&[u8]) -> (Encoding,usize)
+{
+  if bytes.starts_with(&[0xEF,0xBB,0xBF]) {(Encoding::Utf8,3)}
+  else if bytes.starts_with(&[0xFF,0xFE]) {(Encoding::Utf16Le,2)}
+  else if bytes.starts_with(&[0xFE,0xFF]) {(Encoding::Utf16Be,2)}
+  else {(Encoding::Utf8,0)}
+}
+
+// decodes bytes, which do not include a byte order mark, in the given encoding
+fn decode(bytes:Vec<u8>, encoding:Encoding) -> std::io::Result<String>
+{
+  match encoding {
+    Encoding::Utf8 => String::from_utf8(bytes).map_err(invalid_data),
+    Encoding::Latin1 => Ok(bytes.iter().map(|b| *b as char).collect()),
+    Encoding::Utf16Le | Encoding::Utf16Be => {
+      if !bytes.len().is_multiple_of(2) {return Err(invalid_data("odd number of bytes in UTF-16 input"));}
+      let units = bytes.chunks_exact(2).map(|p| {
+        if encoding==Encoding::Utf16Le {u16::from_le_bytes([p[0],p[1]])} else {u16::from_be_bytes([p[0],p[1]])}
+      });
+      char::decode_utf16(units).collect::<Result<String,_>>().map_err(invalid_data)
+    },
+  }
+}
+
 /// Structure to hold contents of a source (such as contents of file).
+/// Besides files, a source can be created from a string, from standard
+/// input or from any reader, with a path that may be a synthetic name such
+/// as `<repl:3>`.  Each source also carries a numeric id, zero by default,
+/// that is passed on to the tokenizers created with [StrTokenizer::from_source].
+///
+/// Sources read as bytes are decoded into UTF-8 contents.  A byte order mark
+/// selects UTF-8, UTF-16LE or UTF-16BE, and is removed from the contents;
+/// without one, the input must be valid UTF-8, and an [std::io::Error] of
+/// kind [InvalidData](std::io::ErrorKind::InvalidData) is returned
+/// otherwise.  Other encodings, such as Latin-1, must be selected with
+/// [LexSource::with_encoding].  The encoding of a source is returned by
+/// [LexSource::get_encoding].
+///
+/// The byte positions at which lines start are indexed the first time they
+/// are needed, so that [LexSource::offset_to_line_col] and related
+/// functions take logarithmic time.  Lines are terminated by `\n`, `\r\n`
+/// or `\r`, as in [StrTokenizer] without [StrTokenizer::unicode_newlines].
 pub struct LexSource<'t>
 {
-   pathname:&'t str,
-   contents:String,
+   pathname:Cow<'t,str>,
+   contents:SourceText,
+   id:usize,
+   line_starts:OnceLock<Vec<usize>>,
+   encoding:Encoding,
+   bom:bool,
 }
 impl<'t> LexSource<'t>
 {
-  /// creates a new LexSource struct with given source path,
-  /// reads contents into struct using [std::fs::read_to_string]
+  fn make(pathname:Cow<'t,str>, contents:SourceText, encoding:Encoding, bom:bool) -> LexSource<'t>
+  {
+     LexSource { pathname, contents, id:0, line_starts:OnceLock::new(), encoding, bom }
+  }
+  /// creates a new LexSource struct with given source path, reads contents
+  /// into struct using [std::fs::read], and decodes them after detecting
+  /// their encoding from a byte order mark.  Contents without a byte order
+  /// mark that are not valid UTF-8 are an error of kind
+  /// [InvalidData](std::io::ErrorKind::InvalidData).
   pub fn new(path:&'t str) -> std::io::Result<LexSource<'t>>
   {
-     let tryread=std::fs::read_to_string(path);
-     //println!("READTHIS: {:?}",&tryread);
-     match tryread {
-       Ok(st) => {
-         Ok(LexSource {
-           pathname:path,
-           contents:st,
-         })
-       },
-       Err(e) => {Err(e)}
-     }//match
+     LexSource::from_bytes(path,std::fs::read(path)?)
   }//new
-//  /// sets the numerical id of this source.
-//  pub fn set_id(&mut self, id:usize) {self.id=id;}
-//  pub fn get_id(&self)->usize {self.id}
+  /// creates a new LexSource from the file at the given path, decoding its
+  /// contents in the given encoding.  A byte order mark for that encoding
+  /// is removed.  This is needed for instance for UTF-16 files without a
+  /// byte order mark, or Latin-1 files that happen to be valid UTF-8.
+  pub fn with_encoding(path:&'t str, encoding:Encoding) -> std::io::Result<LexSource<'t>>
+  {
+     let mut bytes = std::fs::read(path)?;
+     let bomlen = match (encoding,detect_encoding(&bytes)) {
+       (e,(d,n)) if n>0 && e==d => n,
+       _ => 0,
+     };
+     bytes.drain(..bomlen);
+     Ok(LexSource::make(Cow::Borrowed(path),SourceText::Owned(decode(bytes,encoding)?),encoding,bomlen>0))
+  }
+  /// creates a LexSource from bytes in memory, with the given path or
+  /// name, decoding them as by [LexSource::new]
+  pub fn from_bytes<P:Into<Cow<'t,str>>>(path:P, mut bytes:Vec<u8>) -> std::io::Result<LexSource<'t>>
+  {
+     let (encoding,bomlen) = detect_encoding(&bytes);
+     bytes.drain(..bomlen);
+     Ok(LexSource::make(path.into(),SourceText::Owned(decode(bytes,encoding)?),encoding,bomlen>0))
+  }
+  /// creates a LexSource that memory-maps the file at the given path
+  /// instead of copying it, so that the time and memory needed to create
+  /// the source do not grow with the size of the file (beyond checking
+  /// once that it is valid UTF-8).  A [StrTokenizer] created with
+  /// [StrTokenizer::from_source] borrows directly from the mapping.
+  /// UTF-16 files with a byte order mark are decoded into memory instead,
+  /// and other files that are not UTF-8 are an error, as with
+  /// [LexSource::new].  This function requires the `mmap` feature.
+  ///
+  /// # Safety
+  /// The file must not be modified or truncated, by this or any other
+  /// process, while the LexSource exists.  See [memmap2::Mmap::map].
+  #[cfg(feature = "mmap")]
+  pub unsafe fn from_mmap(path:&'t str) -> std::io::Result<LexSource<'t>>
+  {
+     let file = std::fs::File::open(path)?;
+     let mapped = memmap2::Mmap::map(&file)?;
+     match detect_encoding(&mapped) {
+       (Encoding::Utf8,bomlen) => {
+         if let Err(e) = std::str::from_utf8(&mapped[bomlen..]) { return Err(invalid_data(e)); }
+         Ok(LexSource::make(Cow::Borrowed(path),SourceText::Mapped(mapped,bomlen),Encoding::Utf8,bomlen>0))
+       },
+       _ => LexSource::from_bytes(path,mapped.to_vec()),
+     }
+  }
+  /// creates a LexSource from a string already in memory, with the given
+  /// path or name, such as `"<repl:3>"`.  A leading byte order mark is
+  /// removed.
+  pub fn from_string<P:Into<Cow<'t,str>>>(path:P, mut contents:String) -> LexSource<'t>
+  {
+     let bom = contents.starts_with('\u{FEFF}');
+     if bom {contents.drain(..3);}
+     LexSource::make(path.into(),SourceText::Owned(contents),Encoding::Utf8,bom)
+  }
+  /// creates a LexSource by reading all of the given reader, with the
+  /// given path or name, decoding its contents as by [LexSource::from_bytes]
+  pub fn from_reader<P:Into<Cow<'t,str>>, R:std::io::Read>(path:P, mut reader:R) -> std::io::Result<LexSource<'t>>
+  {
+     let mut bytes = Vec::new();
+     reader.read_to_end(&mut bytes)?;
+     LexSource::from_bytes(path,bytes)
+  }
+  /// creates a LexSource by reading all of standard input, with the
+  /// path `"<stdin>"`
+  pub fn from_stdin() -> std::io::Result<LexSource<'t>>
+  {
+     LexSource::from_reader("<stdin>",std::io::stdin().lock())
+  }
+  /// returns the encoding the source was decoded from
+  pub fn get_encoding(&self) -> Encoding {self.encoding}
+  /// whether the source started with a byte order mark, which is not part
+  /// of the contents
+  pub fn has_bom(&self) -> bool {self.bom}
+  /// sets the numerical id of this source.
+  pub fn set_id(&mut self, id:usize) {self.id=id;}
+  /// returns the numerical id of this source, zero unless set.
+  pub fn get_id(&self)->usize {self.id}
   /// retrieves entire contents of lexsource
-  pub fn get_contents(&self)->&str {&self.contents}
+  pub fn get_contents(&self)->&str
+  {
+    match &self.contents {
+      SourceText::Owned(st) => st,
+      #[cfg(feature = "mmap")]
+      SourceText::Mapped(m,start) => unsafe {std::str::from_utf8_unchecked(&m[*start..])},
+    }
+  }
   /// retrieves original path (such as filename) of this source
-  pub fn get_path(&self)->&str {self.pathname}
+  pub fn get_path(&self)->&str {&self.pathname}
+  // byte positions at which lines start, built on first use
+  fn line_starts(&self) -> &[usize]
+  {
+    self.line_starts.get_or_init(|| {
+      let mut starts = vec![0];
+      let contents = self.get_contents();
+      let mut i = 0;
+      while let Some((b,_,n)) = find_line_break(contents,i,false) { i=b+n; starts.push(i); }
+      starts
+    })
+  }
+  // position of the line terminator of the line that starts at start
+  fn line_end(&self, start:usize) -> usize
+  {
+    let contents = self.get_contents();
+    find_line_break(contents,start,false).map(|(i,_,_)|i).unwrap_or(contents.len())
+  }
+  /// the number of lines of the source; a final newline starts an empty line
+  pub fn line_count(&self) -> usize {self.line_starts().len()}
+  /// converts a byte offset into line and column numbers (starting at 1),
+  /// counted in the same way as by [StrTokenizer].  Returns None if the
+  /// offset is past the end of the contents.
+  pub fn offset_to_line_col(&self, offset:usize) -> Option<(usize,usize)>
+  {
+    if offset>self.get_contents().len() {return None;}
+    let starts = self.line_starts();
+    let line = starts.partition_point(|s| *s<=offset);
+    Some((line, offset-starts[line-1]+1))
+  }
+  /// converts line and column numbers (starting at 1) into a byte offset.
+  /// Returns None if there is no such line, or if the column is past the
+  /// end of the line (the column of its newline is the last valid one).
+  pub fn line_col_to_offset(&self, line:usize, column:usize) -> Option<usize>
+  {
+    if line==0 || column==0 {return None;}
+    let starts = self.line_starts();
+    let start = *starts.get(line-1)?;
+    let end = self.line_end(start);
+    let offset = start+column-1;
+    if offset<=end {Some(offset)} else {None}
+  }
+  /// returns the text of the given line (starting at 1), without the
+  /// terminating newline, or None if there is no such line
+  pub fn line_text(&self, line:usize) -> Option<&str>
+  {
+    if line==0 {return None;}
+    let starts = self.line_starts();
+    let start = *starts.get(line-1)?;
+    let end = self.line_end(start);
+    Some(&self.get_contents()[start..end])
+  }
 }//impl LexSource
 impl<'t> StrTokenizer<'t>
 {
    /// creates a StrTokenizer from a [LexSource] structure that contains
    /// a string representing the contents of the source, and
-   /// calls [StrTokenizer::set_input] to reference that string.
+   /// calls [StrTokenizer::set_input] to reference that string.  The
+   /// source path and id of the tokenizer are those of the LexSource.
    /// The proper way to create a tokenizer that reads from a file is therefore:
    ///   ```ignore
    ///   let source = LexSource::new(source_path).unwrap();
@@ -391,10 +1805,13 @@ impl<'t> StrTokenizer<'t>
    pub fn from_source(ls:&'t LexSource<'t>) ->StrTokenizer<'t>
    {
       let mut stk = StrTokenizer::new();
-      stk.set_input(ls.contents.as_str());
+      stk.set_input(ls.get_contents());
+      stk.set_source(ls.get_path());
+      stk.set_source_id(ls.get_id());
       stk
    }
    /// creates a string tokenizer and sets input to give str.
+   #[allow(clippy::should_implement_trait)]
    pub fn from_str(s:&'t str) -> StrTokenizer<'t>
    {
       let mut stk = StrTokenizer::new();
@@ -0,0 +1,71 @@
+// Tests of saving and restoring the state of tokenizers, as done by
+// backtracking parsers
+use basic_lexer::*;
+use basic_lexer::RawToken::*;
+
+#[test]
+fn restore_replays_the_same_tokens()
+{
+  let input = "a = /* c\n */ 1;\nb = \"s\nt\" + 2;";
+  let mut stk = StrTokenizer::from_str(input);
+  stk.add_single(';');
+  stk.next();
+  let saved = stk.checkpoint();
+  assert_eq!((saved.position(),saved.line(),saved.line_start()), (1,1,0));
+  let first:Vec<_> = stk.by_ref().collect();
+  let end = stk.checkpoint();
+  stk.restore(saved);
+  assert_eq!((stk.line(),stk.column(),stk.position()), (1,2,1));
+  let again:Vec<_> = stk.by_ref().collect();
+  assert_eq!(first, again);
+  assert_eq!(stk.checkpoint(), end);
+  assert_eq!(end.line(), 4);
+}
+
+#[test]
+fn restore_discards_peeked_tokens()
+{
+  let mut stk = StrTokenizer::from_str("x y z");
+  let saved = stk.checkpoint();
+  assert_eq!(stk.next(), Some((Alphanum("x"),1,1)));
+  assert_eq!(stk.peek(1), Some(&(Alphanum("z"),1,5)));
+  stk.restore(saved);
+  assert_eq!(stk.next(), Some((Alphanum("x"),1,1)));
+  assert_eq!(stk.next(), Some((Alphanum("y"),1,3)));
+}
+
+#[test]
+fn restore_brings_back_errors_and_previous_token()
+{
+  let mut stk = StrTokenizer::from_str("n / 2 / \"open");
+  stk.set_quiet(true);
+  stk.set_regex_literals(true);
+  stk.next();
+  let saved = stk.checkpoint();
+  assert_eq!(stk.previous(), Some((TokenKind::Alphanum,"n")));
+  let tokens:Vec<_> = stk.by_ref().map(|(t,_,_)|t).collect();
+  // '/' after an operand is a division, so there is no regex literal
+  assert_eq!(tokens, vec![Symbol("/"),Num(2),Symbol("/"),LexError]);
+  assert!(stk.error_diagnostic().is_some());
+  stk.restore(saved);
+  assert_eq!(stk.error_diagnostic(), None);
+  assert_eq!(stk.previous(), Some((TokenKind::Alphanum,"n")));
+  assert_eq!(stk.next(), Some((Symbol("/"),1,3)));
+}
+
+#[test]
+fn file_tokenizer_restores_its_position()
+{
+  let path = std::env::temp_dir().join("basic_lexer_checkpoint.txt");
+  std::fs::write(&path, "a b\n\"multi\nline\" c\nd").unwrap();
+  let mut ft = File_tokenizer::new(path.to_str().unwrap());
+  assert_eq!(ft.next(), Some(Token::Alphanum("a".to_string())));
+  let saved = ft.checkpoint().unwrap();
+  let line = ft.line_number();
+  let first:Vec<_> = ft.by_ref().collect();
+  assert_eq!(first.len(), 4);
+  ft.restore(&saved).unwrap();
+  assert_eq!(ft.line_number(), line);
+  let again:Vec<_> = ft.by_ref().collect();
+  assert_eq!(first, again);
+}
@@ -0,0 +1,126 @@
+// Tests of the basic_lexer command-line driver
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command,Output,Stdio};
+
+// runs the driver with the given arguments and standard input
+fn run(args:&[&str], input:&str) -> Output
+{
+  let mut child = Command::new(env!("CARGO_BIN_EXE_basic_lexer"))
+    .args(args)
+    .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped())
+    .spawn().unwrap();
+  // the driver may exit without reading its input
+  let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+  child.wait_with_output().unwrap()
+}
+
+fn stdout(out:&Output) -> String { String::from_utf8(out.stdout.clone()).unwrap() }
+fn stderr(out:&Output) -> String { String::from_utf8(out.stderr.clone()).unwrap() }
+
+// a file in the temporary directory, unique to the test
+fn temp_file(name:&str, contents:&str) -> PathBuf
+{
+  let path = std::env::temp_dir().join(format!("basic_lexer_cli_{}_{}",std::process::id(),name));
+  std::fs::write(&path,contents).unwrap();
+  path
+}
+
+#[test]
+fn text_format_from_stdin()
+{
+  let out = run(&["--keywords","if","--operators","== ;"],"if x==1;");
+  assert_eq!(out.status.code(), Some(0));
+  assert_eq!(stdout(&out),
+    "<stdin>:1:1\tKeyword\t\"if\"\n<stdin>:1:4\tAlphanum\t\"x\"\n<stdin>:1:5\tSymbol\t\"==\"\n<stdin>:1:7\tNum\t\"1\"\n<stdin>:1:8\tSymbol\t\";\"\n");
+}
+
+#[test]
+fn jsonl_format()
+{
+  let out = run(&["-f","jsonl"],"x \"a\\\"b\" 2\n");
+  assert_eq!(out.status.code(), Some(0));
+  let lines:Vec<_> = stdout(&out).lines().map(|l|l.to_owned()).collect();
+  assert_eq!(lines, vec![
+    r#"{"file":"<stdin>","line":1,"column":1,"kind":"Alphanum","value":"x"}"#,
+    r#"{"file":"<stdin>","line":1,"column":3,"kind":"Strlit","value":"\"a\\\"b\""}"#,
+    r#"{"file":"<stdin>","line":1,"column":10,"kind":"Num","value":2}"#,
+  ]);
+}
+
+#[test]
+fn table_format()
+{
+  let out = run(&["--format","table"],"ab 12");
+  assert_eq!(out.status.code(), Some(0));
+  assert_eq!(stdout(&out),
+    "<stdin>\n  LINE  COLUMN  KIND      VALUE\n     1       1  Alphanum  \"ab\"\n     1       4  Num       \"12\"\n");
+}
+
+#[test]
+fn comment_options_and_keep_flags()
+{
+  let out = run(&["--line-comment","#","--keep-comments","--keep-newline"],"a # note\nb");
+  assert_eq!(out.status.code(), Some(0));
+  let kinds:Vec<_> = stdout(&out).lines().map(|l| l.split('\t').nth(1).unwrap().to_owned()).collect();
+  assert_eq!(kinds, vec!["Alphanum","Verbatim","Newline","Alphanum"]);
+  let out = run(&["--multiline-comment","(*","*)"],"a (* b *) c");
+  assert_eq!(stdout(&out).lines().count(), 2);
+  let out = run(&["--keep-whitespace"],"a  b");
+  assert!(stdout(&out).contains("Whitespace"));
+}
+
+#[test]
+fn files_are_named_in_output()
+{
+  let path = temp_file("files.txt","x\ny");
+  let name = path.to_str().unwrap();
+  let out = run(&[name],"");
+  assert_eq!(out.status.code(), Some(0));
+  assert_eq!(stdout(&out), format!("{0}:1:1\tAlphanum\t\"x\"\n{0}:2:1\tAlphanum\t\"y\"\n",name));
+  std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn lexical_errors_exit_with_status_1()
+{
+  let out = run(&[],"a \"unclosed");
+  assert_eq!(out.status.code(), Some(1));
+  assert!(stdout(&out).contains("LexError"));
+  assert!(stderr(&out).contains("error"));
+}
+
+#[test]
+fn usage_errors_exit_with_status_2()
+{
+  for args in [&["--bogus"][..], &["-f","xml"], &["--operators","==="], &["--multiline-comment","/*",""], &["--keywords"]] {
+    let out = run(args,"x");
+    assert_eq!(out.status.code(), Some(2), "{:?}", args);
+    assert!(stderr(&out).contains("usage:"), "{:?}", args);
+    assert!(out.stdout.is_empty());
+  }
+  let out = run(&["/nonexistent/input.c"],"");
+  assert_eq!(out.status.code(), Some(2));
+  assert!(stderr(&out).starts_with("basic_lexer: /nonexistent/input.c:"));
+}
+
+#[test]
+fn help_exits_successfully()
+{
+  let out = run(&["--help"],"");
+  assert_eq!(out.status.code(), Some(0));
+  assert!(stdout(&out).starts_with("usage: basic_lexer"));
+}
+
+#[test]
+fn spec_file_and_earlier_flags()
+{
+  let spec = temp_file("spec.lex","line_comment ;\nsingles ( )\nkeywords define\n");
+  let out = run(&["--keep-newline","--spec",spec.to_str().unwrap()],"(define x) ; c\n");
+  assert_eq!(out.status.code(), Some(0));
+  let kinds:Vec<_> = stdout(&out).lines().map(|l| l.split('\t').nth(1).unwrap().to_owned()).collect();
+  assert_eq!(kinds, vec!["Symbol","Keyword","Alphanum","Symbol"]);
+  std::fs::remove_file(&spec).unwrap();
+  let out = run(&["--spec",spec.to_str().unwrap()],"");
+  assert_eq!(out.status.code(), Some(2));
+}
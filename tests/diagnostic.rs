@@ -0,0 +1,78 @@
+// Tests of the rendering of diagnostics
+use basic_lexer::*;
+
+#[test]
+fn renders_snippet_with_carets()
+{
+  let text = "let a = 1;\nlet b = \"abc;\n";
+  let d = Diagnostic::error("unclosed string",19,20).with_note("strings may span several lines");
+  assert_eq!(d.render_text("input.txt",text,false),
+"error: unclosed string
+ --> input.txt:2:9
+  |
+2 | let b = \"abc;
+  |         ^
+  = note: strings may span several lines
+");
+}
+
+#[test]
+fn lines_end_with_crlf_and_lone_cr()
+{
+  let text = "a\r\nb\rc = 1";
+  let d = Diagnostic::warning("unused",5,6);
+  let out = d.render_text("x",text,false);
+  assert!(out.starts_with("warning: unused\n --> x:3:1\n"), "{}", out);
+  assert!(out.contains("3 | c = 1\n  | ^\n"), "{}", out);
+}
+
+#[test]
+fn ranges_over_several_lines()
+{
+  let text = "one\ntwo\nthree\nfour\nfive\nsix\n";
+  // a range that ends with a newline does not extend to the next line
+  let out = Diagnostic::error("e",4,8).render_text("p",text,false);
+  assert!(out.contains("2 | two\n  | ^^^\n"), "{}", out);
+  assert!(!out.contains("3 |"), "{}", out);
+  let out = Diagnostic::error("e",0,27).render_text("p",text,false);
+  assert!(out.contains("4 | four\n"), "{}", out);
+  assert!(!out.contains("5 |"), "{}", out);
+  assert!(out.contains("...\n"), "{}", out);
+}
+
+#[test]
+fn tabs_follow_the_tokenizer()
+{
+  let text = "\tx = \"open";
+  let mut stk = StrTokenizer::from_str(text);
+  stk.set_quiet(true);
+  let tokens:Vec<_> = stk.by_ref().collect();
+  let d = stk.error_diagnostic().unwrap();
+  // columns in bytes, with the tab printed as it is
+  assert_eq!(tokens.last(), Some(&(RawToken::LexError,1,6)));
+  assert!(d.render_for(&stk,false).contains(" --> :1:6\n  |\n1 | \tx = \"open\n  | \t    ^\n"));
+  stk.set_input(text);
+  stk.set_tab_width(8);
+  let tokens:Vec<_> = stk.by_ref().collect();
+  assert_eq!(tokens.last(), Some(&(RawToken::LexError,1,13)));
+  let out = d.render_for(&stk,false);
+  assert!(out.contains(" --> :1:13\n  |\n1 |         x = \"open\n  |             ^\n"), "{}", out);
+}
+
+#[test]
+fn unicode_newlines_follow_the_tokenizer()
+{
+  let text = "a\u{2028}b";
+  let mut stk = StrTokenizer::from_str(text);
+  let d = Diagnostic::error("here",4,5);
+  assert!(d.render_for(&stk,false).contains(" --> :1:5\n"));
+  stk.unicode_newlines = true;
+  assert!(d.render_for(&stk,false).contains(" --> :2:1\n"));
+}
+
+#[test]
+fn severities_have_labels()
+{
+  let labels:Vec<_> = [Severity::Error,Severity::Warning,Severity::Note,Severity::Help].iter().map(|s|s.label()).collect();
+  assert_eq!(labels, vec!["error","warning","note","help"]);
+}
@@ -0,0 +1,53 @@
+// Generated by basic_lexer from a lexer specification, do not edit.
+#[allow(unused_imports)]
+use basic_lexer::{CharSet, Escape, NumberFormat, StateTable, StringRule, TableLexer, TokenClass, TokenKind, Transition};
+use std::borrow::Cow;
+
+/// the states of the lexer, starting with the initial state
+pub static STATES: &[StateTable<'static>] = &[
+    StateTable {
+        name: "main",
+        singles: Cow::Borrowed(&['(', ')', '[', ']', '{', '}', ';', ',', '=', '<', '>', '+', '-', '*', '/']),
+        doubles: Cow::Borrowed(&["==", "!=", "<=", ">=", "&&", "||", "++", "--"]),
+        keywords: Cow::Borrowed(&["if", "else", "while", "for", "return"]),
+        line_comment: "//",
+        multiline_comment: ("/*", "*/"),
+        strings: Cow::Borrowed(&[
+            StringRule { start: "\"", end: "\"", escape: Escape::Backslash },
+            StringRule { start: "'", end: "'", escape: Escape::Doubled },
+        ]),
+        classes: Cow::Borrowed(&[
+            TokenClass { kind: TokenKind::Alphanum, start: CharSet::new("a-zA-Z_$"), cont: CharSet::new("a-zA-Z0-9_$") },
+            TokenClass { kind: TokenKind::Symbol, start: CharSet::new("@"), cont: CharSet::new("@") },
+        ]),
+        transitions: Cow::Borrowed(&[
+            Transition { on: "`", to: 1 },
+        ]),
+        numbers: NumberFormat { hex: true, binary: false, octal: false, exponents: true, underscores: true },
+        text: false,
+    },
+    StateTable {
+        name: "template",
+        singles: Cow::Borrowed(&[]),
+        doubles: Cow::Borrowed(&[]),
+        keywords: Cow::Borrowed(&[]),
+        line_comment: "",
+        multiline_comment: ("", ""),
+        strings: Cow::Borrowed(&[]),
+        classes: Cow::Borrowed(&[]),
+        transitions: Cow::Borrowed(&[
+            Transition { on: "`", to: 0 },
+        ]),
+        numbers: NumberFormat { hex: true, binary: false, octal: false, exponents: false, underscores: false },
+        text: true,
+    },
+];
+
+/// creates a lexer for the given input, driven by the static tables
+pub fn make_tokenizer(input: &str) -> TableLexer<'static, '_> {
+    let mut lexer = TableLexer::new(STATES, input);
+    lexer.keep_whitespace = false;
+    lexer.keep_newline = false;
+    lexer.keep_comment = true;
+    lexer
+}
@@ -0,0 +1,17 @@
+/* sum of squares */
+#include <stdio.h>
+
+static int square(int x) { return x*x; }
+
+int main(void)
+{
+  unsigned long total = 0x1F;   // hex
+  double scale = 2.5e-3;
+  char nl = '\n', q = '\'';
+  for (int i = 0; i <= 10; i++) {
+    total += square(i) >> 1;
+    if (total != 0 && i->next) continue;
+  }
+  printf("total: %lu\t\"done\"\n", total);
+  return 0;
+}
@@ -0,0 +1,17 @@
+// regular expressions and divisions
+import { parse } from "./parse.js";
+
+const WORD = /[a-z_$][\w$]*/gi;
+const SLASH = /\/|[/]/;
+
+function ratio(total, $count) {
+  const half = total / 2, rest = (total - $count) / $count;
+  let avg = values.length ? sum(values) / values.length : 0;
+  avg /= 1_000;
+  if (!/^\d+$/.test(`${total}`)) return /x/.source;
+  return [half, rest][0] / avg ?? 0x1F;
+}
+
+/* the last token
+   of the file */
+export default { ratio, matches: s => s.split(/\s+/).filter(w => WORD.test(w)) };
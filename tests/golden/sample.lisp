@@ -0,0 +1,14 @@
+; factorial
+(define (fact n)
+  (if (= n 0)
+      1
+      (* n (fact (- n 1)))))
+#| block
+   #| nested |# comment |#
+(display "fact of 5: ")
+(let* ((xs '(1 2 3.5 -4))
+       (v (list->vector xs)))
+  (if (null? xs) #(0) `(,@xs ,(fact 5))))
+(set-car! pair 'x)
+(map #'car lst)
+(apply + 1 ...)
@@ -0,0 +1,12 @@
+# greet everybody
+def greet(names, sep=', '):
+    """Return a greeting.
+
+    Names are joined with sep."""
+    if not names:
+        return None
+    total = 1_000 + 0x10 + 1.5e2
+    return 'Hello, ' + sep.join(names) + "!"
+
+class Greeter:
+    pass
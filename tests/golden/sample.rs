@@ -0,0 +1,18 @@
+//! Example module
+use std::collections::HashMap;
+
+/* block
+   comment */
+pub fn count<'a>(words: &[&'a str]) -> HashMap<&'a str, usize> {
+    let mut map = HashMap::new();
+    for w in words.iter() {
+        *map.entry(*w).or_insert(0) += 1;
+    }
+    let mask = 0b1010_1010 | 0o17 | 0xFF;
+    let big = 1_000_000;
+    let ratio = 6.02e23;
+    let c = 'x';
+    let range = 0..10;
+    match mask { 0 => println!("zero: {}", c), _ => {} }
+    map
+}
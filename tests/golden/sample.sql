@@ -0,0 +1,10 @@
+-- customers with large orders
+SELECT c.name, SUM(o.total) AS spent
+FROM customers c
+  INNER JOIN orders o ON o.customer_id = c.id
+WHERE o.total >= 100.5 AND c.note <> 'it''s fine'
+/* grouped
+   by name */
+group by c.name
+HAVING count(*) > 2
+ORDER BY spent DESC;
@@ -0,0 +1,125 @@
+// Tests of hooks that scan tokens with hand-written code
+use std::sync::{Arc,Mutex};
+use basic_lexer::*;
+use basic_lexer::RawToken::*;
+
+fn tokens<'t>(stk:&mut StrTokenizer<'t>, input:&'t str) -> Vec<(RawToken<'t>,usize,usize)>
+{
+  stk.set_input(input);
+  stk.set_quiet(true);
+  stk.collect()
+}
+
+#[test]
+fn hooks_take_precedence_over_comments()
+{
+  let mut stk = StrTokenizer::new();
+  // "///" doc comments are kept even though comments are not
+  stk.add_hook("///", |cx| {
+    let rest = cx.rest();
+    let len = rest.find('\n').unwrap_or(rest.len());
+    Some((Verbatim(&rest[..len]),len))
+  });
+  let toks = tokens(&mut stk,"a // c\n/// doc\n/* x */ b");
+  assert_eq!(toks, vec![(Alphanum("a"),1,1),(Verbatim("/// doc"),2,1),(Alphanum("b"),3,9)]);
+}
+
+#[test]
+fn hooks_run_after_whitespace_is_skipped()
+{
+  let mut stk = StrTokenizer::new();
+  stk.add_hook(" ", |_| Some((Symbol("space"),1)));
+  stk.add_hook("@", |cx| Some((Num(cx.column() as i64),1)));
+  let toks = tokens(&mut stk,"a   @\n  @");
+  assert_eq!(toks, vec![(Alphanum("a"),1,1),(Num(5),1,5),(Num(3),2,3)]);
+  stk.keep_whitespace = true;
+  let toks:Vec<_> = tokens(&mut stk,"a  @").into_iter().map(|(t,_,_)|t).collect();
+  assert_eq!(toks, vec![Alphanum("a"),Whitespace(2),Num(4)]);
+}
+
+#[test]
+fn first_hook_that_returns_a_token_wins()
+{
+  let mut stk = StrTokenizer::new();
+  stk.add_hook("#", |cx| if cx.rest().starts_with("#!") {Some((Keyword("shebang"),2))} else {None});
+  stk.add_hook("#", |_| Some((Keyword("hash"),0)));  // zero length is ignored
+  stk.add_hook("#", |_| Some((Keyword("hash"),1)));
+  let toks:Vec<_> = tokens(&mut stk,"#! # x").into_iter().map(|(t,_,_)|t).collect();
+  assert_eq!(toks, vec![Keyword("shebang"),Keyword("hash"),Alphanum("x")]);
+}
+
+#[test]
+fn lengths_off_char_boundaries_are_errors()
+{
+  let mut stk = StrTokenizer::new();
+  stk.add_hook("λ", |_| Some((Symbol("λ"),1)));
+  let toks = tokens(&mut stk,"a λ b");
+  assert_eq!(toks, vec![(Alphanum("a"),1,1),(LexError,1,3)]);
+  assert_eq!(stk.error_diagnostic().unwrap().message, "invalid length returned by hook");
+}
+
+#[test]
+fn lengths_past_the_end_are_errors()
+{
+  let mut stk = StrTokenizer::new();
+  stk.add_hook("$", |cx| Some((Symbol("$"),cx.rest().len()+1)));
+  let toks = tokens(&mut stk,"x $\ny");
+  assert_eq!(toks, vec![(Alphanum("x"),1,1),(LexError,1,3)]);
+  assert_eq!(stk.error_diagnostic().unwrap().message, "invalid length returned by hook");
+  assert_eq!(stk.line(), 2);
+}
+
+#[test]
+fn hooks_can_return_errors_and_span_lines()
+{
+  let mut stk = StrTokenizer::new();
+  stk.add_hook("<<", |cx| {
+    let rest = cx.rest();
+    match rest.find(">>") {
+      Some(i) => Some((Verbatim(&rest[..i+2]),i+2)),
+      None => Some((LexError,rest.len())),
+    }
+  });
+  let toks = tokens(&mut stk,"<<a\nb>> c <<d");
+  assert_eq!(toks, vec![(Verbatim("<<a\nb>>"),1,1),(Alphanum("c"),2,5),(LexError,2,7)]);
+  assert_eq!(stk.error_diagnostic().unwrap().message, "invalid token");
+}
+
+#[test]
+fn context_has_the_previous_token()
+{
+  let seen = Arc::new(Mutex::new(Vec::new()));
+  let record = Arc::clone(&seen);
+  let mut stk = StrTokenizer::new();
+  stk.add_single('.');
+  stk.add_hook("#", move |cx| {
+    record.lock().unwrap().push(cx.previous().map(|(k,s)| (k,s.to_owned())));
+    match cx.previous() {
+      Some((TokenKind::Symbol,".")) => {
+        let rest = cx.rest();
+        let len = rest[1..].find(|c:char| !c.is_alphanumeric()).map_or(rest.len(),|i|i+1);
+        Some((Alphanum(&rest[..len]),len))
+      },
+      _ => None,
+    }
+  });
+  stk.keep_comment = true;
+  let toks:Vec<_> = tokens(&mut stk,"#a this. /* c */ #count x#").into_iter().map(|(t,_,_)|t).collect();
+  assert_eq!(toks, vec![Symbol("#"),Alphanum("a"),Alphanum("this"),Symbol("."),Verbatim("/* c */"),
+                        Alphanum("#count"),Alphanum("x"),Symbol("#")]);
+  // comments are not previous tokens
+  assert_eq!(*seen.lock().unwrap(), vec![None, Some((TokenKind::Symbol,".".to_owned())),
+                                          Some((TokenKind::Alphanum,"x".to_owned()))]);
+}
+
+#[test]
+fn context_positions()
+{
+  let mut stk = StrTokenizer::new();
+  stk.add_hook("?", |cx| {
+    assert_eq!(&cx.input()[cx.position()..], cx.rest());
+    Some((Num((cx.line()*100+cx.position()) as i64),1))
+  });
+  let toks:Vec<_> = tokens(&mut stk,"?\n ab ?").into_iter().map(|(t,_,_)|t).collect();
+  assert_eq!(toks, vec![Num(100),Alphanum("ab"),Num(206)]);
+}
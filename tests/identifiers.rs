@@ -0,0 +1,113 @@
+// Tests of identifier characters, signed numbers and reader macros
+use basic_lexer::*;
+use basic_lexer::RawToken::*;
+
+fn lex<'t>(stk:&mut StrTokenizer<'t>, input:&'t str) -> Vec<RawToken<'t>>
+{
+  stk.set_input(input);
+  stk.map(|(t,_,_)|t).collect()
+}
+
+#[test]
+fn default_identifiers_are_unchanged()
+{
+  let mut stk = StrTokenizer::new();
+  assert_eq!(lex(&mut stk,"null? a-b -4"),
+    vec![Alphanum("null"),Symbol("?"),Alphanum("a"),Symbol("-"),Alphanum("b"),Symbol("-"),Num(4)]);
+}
+
+#[test]
+fn extra_start_and_continue_characters()
+{
+  let mut stk = StrTokenizer::new();
+  stk.set_identifier_chars("?!-*","?!->");
+  assert_eq!(lex(&mut stk,"null? set-car! list->vector * ?x a*b"),
+    vec![Alphanum("null?"),Alphanum("set-car!"),Alphanum("list->vector"),Alphanum("*"),
+         Alphanum("?x"),Alphanum("a"),Alphanum("*b")]);
+  // singles are still symbols
+  stk.add_single('!');
+  assert_eq!(lex(&mut stk,"!x"), vec![Symbol("!"),Alphanum("x")]);
+}
+
+#[test]
+fn signs_that_start_identifiers_are_signs_of_numbers()
+{
+  let mut stk = StrTokenizer::new();
+  stk.set_identifier_chars("+-","+-");
+  assert_eq!(lex(&mut stk,"-4 +7 -2.5 - -x x-1 1-"),
+    vec![Num(-4),Num(7),Float(-2.5),Alphanum("-"),Alphanum("-x"),Alphanum("x-1"),Num(1),Alphanum("-")]);
+  let mut stk = StrTokenizer::new();
+  stk.set_identifier_chars("-","");
+  stk.set_number_format(NumberFormat{exponents:true, ..NumberFormat::default()});
+  assert_eq!(lex(&mut stk,"-1e2 -0x10 +3"), vec![Float(-100.0),Num(-16),Symbol("+"),Num(3)]);
+  // a sign that is only a continue character is not part of a number
+  let mut stk = StrTokenizer::new();
+  stk.set_identifier_chars("","-");
+  assert_eq!(lex(&mut stk,"-4"), vec![Symbol("-"),Num(4)]);
+}
+
+#[test]
+fn reader_macros_longest_first()
+{
+  let mut stk = StrTokenizer::new();
+  stk.add_reader_macros("' ` , ,@ #'");
+  stk.add_single(',');
+  assert_eq!(lex(&mut stk,"'a `(,b ,@c) #'f 'x'"),
+    vec![ReaderMacro("'"),Alphanum("a"),ReaderMacro("`"),Symbol("("),ReaderMacro(","),Alphanum("b"),
+         ReaderMacro(",@"),Alphanum("c"),Symbol(")"),ReaderMacro("#'"),Alphanum("f"),
+         ReaderMacro("'"),Alphanum("x"),ReaderMacro("'")]);
+  assert_eq!(ReaderMacro(",@").kind(), TokenKind::ReaderMacro);
+  assert_eq!(ReaderMacro(",@").into_owned(), OwnedToken::ReaderMacro(",@".to_owned()));
+  // comments take precedence
+  stk.set_line_comment(";");
+  stk.add_reader_macros(";q");
+  assert_eq!(lex(&mut stk,"a ;q b"), vec![Alphanum("a")]);
+}
+
+#[test]
+fn lisp_preset()
+{
+  let mut stk = StrTokenizer::for_language(Language::Lisp);
+  assert_eq!(lex(&mut stk,"(f '(1 -2) `(,@xs ,y) #(0) #'car) ; c"),
+    vec![Symbol("("),Alphanum("f"),ReaderMacro("'"),Symbol("("),Num(1),Num(-2),Symbol(")"),
+         ReaderMacro("`"),Symbol("("),ReaderMacro(",@"),Alphanum("xs"),ReaderMacro(","),Alphanum("y"),
+         Symbol(")"),ReaderMacro("#("),Num(0),Symbol(")"),ReaderMacro("#'"),Alphanum("car"),Symbol(")")]);
+  assert_eq!(lex(&mut stk,"(- n 1) (1+ x) #| a #| b |# c |# -x"),
+    vec![Symbol("("),Alphanum("-"),Alphanum("n"),Num(1),Symbol(")"),
+         Symbol("("),Num(1),Alphanum("+"),Alphanum("x"),Symbol(")"),Alphanum("-x")]);
+}
+
+#[test]
+fn reader_macros_are_cached_and_rules_hashed()
+{
+  let text = "'(a ,@b)";
+  let mut stk = StrTokenizer::for_language(Language::Lisp);
+  stk.set_input(text);
+  let stream = TokenStream::from_tokenizer(&mut stk);
+  assert_eq!(stream.tokens[0].token, OwnedToken::ReaderMacro("'".to_owned()));
+  assert_eq!(TokenStream::from_bytes(&stream.to_bytes()).unwrap(), stream);
+  let mut a = StrTokenizer::new();
+  let mut b = StrTokenizer::new();
+  b.add_reader_macros("'");
+  assert_ne!(a.config_hash(), b.config_hash());
+  a.add_reader_macros("'");
+  assert_eq!(a.config_hash(), b.config_hash());
+}
+
+#[test]
+fn incremental_edits_next_to_reader_macros()
+{
+  let def = LexerDef::for_language(Language::Lisp);
+  let mut lexer = IncrementalLexer::new(&def);
+  let mut text = String::from("(a , b)");
+  lexer.lex(&text);
+  // ", b" becomes ",@b"
+  text.replace_range(3..5,",@");
+  lexer.edit(&text,TextEdit{start:3, old_end:5, new_end:5});
+  let tokens:Vec<_> = lexer.tokens().iter().map(|t| t.token.clone()).collect();
+  let mut full = IncrementalLexer::new(&def);
+  full.lex(&text);
+  let expected:Vec<_> = full.tokens().iter().map(|t| t.token.clone()).collect();
+  assert_eq!(tokens, expected);
+  assert_eq!(tokens[2], OwnedToken::ReaderMacro(",@".to_owned()));
+}
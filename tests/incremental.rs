@@ -0,0 +1,100 @@
+// Tests of IncrementalLexer, comparing edits with tokenizing the whole text
+use basic_lexer::*;
+
+const PIECES:[&str;26] = ["x","y1","1","2.5","0x1F",".","'a'","'","'\\n'","\"s t\"","\"","//c","/*","*/",
+  "\n","\r\n","\r"," ","\t","==","=","é","(",")","/","\\"];
+
+// a small deterministic random number generator (xorshift)
+struct Rng(u64);
+impl Rng
+{
+  fn next(&mut self, n:usize) -> usize
+  {
+    self.0 ^= self.0<<13; self.0 ^= self.0>>7; self.0 ^= self.0<<17;
+    (self.0 % n as u64) as usize
+  }
+  fn text(&mut self, pieces:&[&str], n:usize) -> String
+  {
+    (0..n).map(|_| pieces[self.next(pieces.len())]).collect()
+  }
+  // a random char boundary of s
+  fn boundary(&mut self, s:&str) -> usize
+  {
+    let mut i = self.next(s.len()+1);
+    while !s.is_char_boundary(i) {i-=1;}
+    i
+  }
+}
+
+fn summary(lexer:&IncrementalLexer) -> Vec<(OwnedToken,usize,usize,usize,usize)>
+{
+  lexer.tokens().iter().map(|t| (t.token.clone(),t.line,t.column,t.start,t.end)).collect()
+}
+
+fn check_random_edits(def:&LexerDef, pieces:&[&str], keep:bool, seed:u64)
+{
+  let mut rng = Rng(seed);
+  let mut text = rng.text(pieces,40);
+  let mut lexer = IncrementalLexer::new(def);
+  lexer.keep_whitespace = keep;
+  lexer.keep_newline = keep;
+  lexer.keep_comment = keep;
+  lexer.lex(&text);
+  for round in 0..300 {
+    let start = rng.boundary(&text);
+    let old_end = start+rng.boundary(&text[start..]).min(6);
+    let old_end = (old_end..=text.len()).find(|i| text.is_char_boundary(*i)).unwrap();
+    let n = rng.next(3);
+    let inserted = rng.text(pieces,n);
+    let before = text.clone();
+    text.replace_range(start..old_end, &inserted);
+    let edit = TextEdit{start, old_end, new_end:start+inserted.len()};
+    let changed = lexer.edit(&text, edit);
+    let mut full = IncrementalLexer::new(def);
+    full.keep_whitespace = keep;
+    full.keep_newline = keep;
+    full.keep_comment = keep;
+    full.lex(&text);
+    assert_eq!(summary(&lexer), summary(&full), "round {} seed {}: {:?} -> {:?} by {:?}", round, seed, before, text, edit);
+    assert!(changed.end<=lexer.tokens().len());
+  }
+}
+
+#[test]
+fn edits_agree_with_full_lex()
+{
+  let mut def = LexerDef::new();
+  def.add_double("==");
+  def.add_single('=');
+  def.add_single('.');
+  for seed in 1..=20 {
+    check_random_edits(&def,&PIECES,false,seed);
+    check_random_edits(&def,&PIECES,true,seed*7919);
+  }
+}
+
+#[test]
+fn edits_agree_with_full_lex_with_long_delimiters()
+{
+  let mut def = LexerDef::new();
+  def.set_line_comment("-----------");
+  def.set_multiline_comments("<!-----------  ----------->");
+  // each dash is a token that may start a comment
+  def.add_single('-');
+  let pieces = ["-","---","-----","<!","<!---","--->",">","x"," ","\n"];
+  for seed in 1..=10 { check_random_edits(&def,&pieces,true,seed); }
+}
+
+#[test]
+fn only_the_edited_region_is_lexed_again()
+{
+  let def = LexerDef::new();
+  let mut lexer = IncrementalLexer::new(&def);
+  let mut text = String::from("a b c d e f g h i j k l m n o p q r s t");
+  lexer.lex(&text);
+  text.replace_range(20..21, "kk");
+  let changed = lexer.edit(&text, TextEdit{start:20, old_end:21, new_end:22});
+  assert!(changed.len()<=6, "{:?}", changed);
+  assert_eq!(lexer.tokens()[10].token, OwnedToken::Alphanum("kk".to_string()));
+  assert_eq!((lexer.tokens()[19].start,lexer.tokens()[19].column), (39,40));
+}
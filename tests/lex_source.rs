@@ -0,0 +1,133 @@
+// Tests of LexSource created from files, strings and readers
+use basic_lexer::*;
+use basic_lexer::RawToken::*;
+
+#[test]
+fn sources_from_strings_have_virtual_paths()
+{
+  let source = LexSource::from_string("<repl:3>", "x+1".to_string());
+  assert_eq!(source.get_path(), "<repl:3>");
+  assert_eq!(source.get_contents(), "x+1");
+  assert_eq!(source.get_id(), 0);
+  let tokens:Vec<_> = StrTokenizer::from_source(&source).map(|(t,_,_)|t).collect();
+  assert_eq!(tokens, vec![Alphanum("x"),Symbol("+"),Num(1)]);
+}
+
+#[test]
+fn byte_order_mark_is_removed_from_strings()
+{
+  let source = LexSource::from_string(String::from("bom.txt"), "\u{FEFF}abc".to_string());
+  assert!(source.has_bom());
+  assert_eq!(source.get_contents(), "abc");
+}
+
+#[test]
+fn sources_from_readers()
+{
+  let source = LexSource::from_reader("<memory>", &b"a b\nc"[..]).unwrap();
+  assert_eq!(source.get_contents(), "a b\nc");
+  assert_eq!(source.get_encoding(), Encoding::Utf8);
+}
+
+#[test]
+fn source_ids_are_passed_to_tokenizers()
+{
+  let mut sources:Vec<LexSource> = (0..3).map(|i| LexSource::from_string(format!("<virtual:{}>",i), format!("v{}",i))).collect();
+  for (i,s) in sources.iter_mut().enumerate() { s.set_id(i+10); }
+  for (i,s) in sources.iter().enumerate() {
+    assert_eq!(s.get_path(), format!("<virtual:{}>",i));
+    let stk = StrTokenizer::from_source(s);
+    assert_eq!(stk.get_source_id(), i+10);
+  }
+}
+
+#[test]
+fn sources_from_files()
+{
+  let path = std::env::temp_dir().join("basic_lexer_lex_source.txt");
+  std::fs::write(&path, "file contents\n").unwrap();
+  let path = path.to_str().unwrap();
+  let source = LexSource::new(path).unwrap();
+  assert_eq!(source.get_path(), path);
+  assert_eq!(source.get_contents(), "file contents\n");
+  assert_eq!(LexSource::new("/no/such/file").err().map(|e|e.kind()), Some(std::io::ErrorKind::NotFound));
+}
+
+#[test]
+fn line_index_converts_offsets_both_ways()
+{
+  let source = LexSource::from_string("lines", "ab\ncd\r\nef\rg\n".to_string());
+  assert_eq!(source.line_count(), 5);
+  let expected = [(0,(1,1)),(2,(1,3)),(3,(2,1)),(5,(2,3)),(7,(3,1)),(10,(4,1)),(11,(4,2)),(12,(5,1))];
+  for (offset,(line,column)) in expected.iter().copied() {
+    assert_eq!(source.offset_to_line_col(offset), Some((line,column)), "offset {}", offset);
+    assert_eq!(source.line_col_to_offset(line,column), Some(offset));
+  }
+  // the \n of a \r\n is past the last valid column of its line
+  assert_eq!(source.offset_to_line_col(6), Some((2,4)));
+  assert_eq!(source.line_col_to_offset(2,4), None);
+  assert_eq!(source.offset_to_line_col(13), None);
+  assert_eq!(source.line_col_to_offset(1,4), None);
+  assert_eq!(source.line_col_to_offset(6,1), None);
+  assert_eq!(source.line_col_to_offset(0,1), None);
+  let lines:Vec<_> = (1..=6).map(|l| source.line_text(l)).collect();
+  assert_eq!(lines, vec![Some("ab"),Some("cd"),Some("ef"),Some("g"),Some(""),None]);
+}
+
+#[test]
+fn line_index_agrees_with_tokenizer()
+{
+  let text = "let a = 1;\n\n  b = \"two\nlines\" c\r\n\td";
+  let source = LexSource::from_string("agree", text.to_string());
+  let mut stk = StrTokenizer::from_source(&source);
+  let mut before = stk.position();
+  while let Some((_,line,column)) = stk.next() {
+    let start = before + text[before..].len() - text[before..].trim_start().len();
+    assert_eq!(source.offset_to_line_col(start), Some((line,column)));
+    before = stk.position();
+  }
+}
+
+#[test]
+fn invalid_utf8_is_an_error()
+{
+  let path = std::env::temp_dir().join("basic_lexer_latin1.txt");
+  std::fs::write(&path, b"caf\xe9 = 1").unwrap();
+  let path = path.to_str().unwrap();
+  assert_eq!(LexSource::new(path).err().map(|e|e.kind()), Some(std::io::ErrorKind::InvalidData));
+  assert!(LexSource::from_bytes("bytes", b"caf\xe9".to_vec()).is_err());
+  assert!(LexSource::from_reader("reader", &b"\xff\xff"[..]).is_err());
+  // Latin-1 must be asked for
+  let source = LexSource::with_encoding(path, Encoding::Latin1).unwrap();
+  assert_eq!(source.get_contents(), "café = 1");
+  assert_eq!(source.get_encoding(), Encoding::Latin1);
+}
+
+#[test]
+fn byte_order_marks_select_the_encoding()
+{
+  let source = LexSource::from_bytes("utf8", b"\xEF\xBB\xBFab".to_vec()).unwrap();
+  assert_eq!((source.get_contents(),source.get_encoding(),source.has_bom()), ("ab",Encoding::Utf8,true));
+  let mut le = vec![0xFF,0xFE];
+  let mut be = vec![0xFE,0xFF];
+  for u in "xé".encode_utf16() { le.extend(u.to_le_bytes()); be.extend(u.to_be_bytes()); }
+  let source = LexSource::from_bytes("le", le).unwrap();
+  assert_eq!((source.get_contents(),source.get_encoding()), ("xé",Encoding::Utf16Le));
+  let source = LexSource::from_bytes("be", be).unwrap();
+  assert_eq!((source.get_contents(),source.get_encoding()), ("xé",Encoding::Utf16Be));
+  let plain = LexSource::from_bytes("plain", b"ab".to_vec()).unwrap();
+  assert_eq!((plain.get_encoding(),plain.has_bom()), (Encoding::Utf8,false));
+  // an odd number of bytes is not UTF-16
+  assert!(LexSource::from_bytes("odd", vec![0xFF,0xFE,0x41]).is_err());
+}
+
+#[test]
+fn utf16_without_byte_order_mark()
+{
+  let path = std::env::temp_dir().join("basic_lexer_utf16be.txt");
+  let bytes:Vec<u8> = "hi".encode_utf16().flat_map(|u| u.to_be_bytes()).collect();
+  std::fs::write(&path, bytes).unwrap();
+  let source = LexSource::with_encoding(path.to_str().unwrap(), Encoding::Utf16Be).unwrap();
+  assert_eq!(source.get_contents(), "hi");
+  assert!(!source.has_bom());
+}
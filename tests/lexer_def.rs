@@ -0,0 +1,71 @@
+// Tests of LexerDef, the rules shared by many tokenizers
+use std::sync::Arc;
+use basic_lexer::*;
+use basic_lexer::RawToken::*;
+
+fn c_like() -> LexerDef<'static>
+{
+  let mut def = LexerDef::new();
+  def.add_single(';');
+  def.add_double("==");
+  def.add_keywords("if else while");
+  def
+}
+
+#[test]
+fn definitions_are_send_and_sync()
+{
+  fn shareable<T:Send+Sync>() {}
+  shareable::<LexerDef<'static>>();
+}
+
+#[test]
+fn tokenizers_share_a_definition()
+{
+  let def = c_like();
+  let a:Vec<_> = def.tokenizer("if x==1;").map(|(t,_,_)|t).collect();
+  let b:Vec<_> = def.tokenizer("while y;").map(|(t,_,_)|t).collect();
+  assert_eq!(a, vec![Keyword("if"),Alphanum("x"),Symbol("=="),Num(1),Symbol(";")]);
+  assert_eq!(b, vec![Keyword("while"),Alphanum("y"),Symbol(";")]);
+}
+
+#[test]
+fn definition_is_borrowed_until_changed()
+{
+  let def = c_like();
+  let mut stk = StrTokenizer::with_def(&def);
+  assert!(std::ptr::eq(stk.get_def(), &def));
+  stk.add_keywords("return");
+  stk.set_input("return if");
+  assert!(!std::ptr::eq(stk.get_def(), &def));
+  let toks:Vec<_> = stk.map(|(t,_,_)|t).collect();
+  assert_eq!(toks, vec![Keyword("return"),Keyword("if")]);
+  // the shared definition is unchanged
+  let toks:Vec<_> = def.tokenizer("return").map(|(t,_,_)|t).collect();
+  assert_eq!(toks, vec![Alphanum("return")]);
+}
+
+#[test]
+fn definition_is_shared_across_threads()
+{
+  let def = Arc::new(c_like());
+  let inputs:[&'static str;3] = ["if a==b;", "else c;", "while d==e;"];
+  let handles:Vec<_> = inputs.iter().copied().map(|input| {
+    let def = Arc::clone(&def);
+    std::thread::spawn(move || def.tokenizer(input).map(|(t,_,_)|t.into_owned()).collect::<Vec<_>>())
+  }).collect();
+  for (input,handle) in inputs.iter().zip(handles) {
+    let expected:Vec<_> = def.tokenizer(input).map(|(t,_,_)|t.into_owned()).collect();
+    assert_eq!(handle.join().unwrap(), expected);
+  }
+}
+
+#[test]
+fn new_tokenizer_has_the_default_definition()
+{
+  let def = LexerDef::default();
+  let with_default:Vec<_> = def.tokenizer("f(x) // c\n{ /* d */ }").collect();
+  let plain:Vec<_> = StrTokenizer::from_str("f(x) // c\n{ /* d */ }").collect();
+  assert_eq!(with_default, plain);
+  assert_eq!(plain.len(), 6);
+}
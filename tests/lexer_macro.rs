@@ -0,0 +1,104 @@
+// Tests of the lexer! macro.  The checks of the rules run at compile time
+// in the macro; here check_lexer_rules is also called at runtime to test
+// that conflicting rules are rejected.
+use basic_lexer::*;
+use basic_lexer::RawToken::*;
+
+lexer! {
+  /// lexer for a small C-like language
+  pub struct CLexer;
+  singles: [';', ',', '(', ')', '{', '}'];
+  doubles: ["==", "<=", "++"];
+  keywords: ["if", "while", "return"];
+  line_comment: "//";
+  multiline_comment: "/*", "*/";
+  strings: [("\"", "\"", Backslash), ("'", "'", Doubled)];
+  classes: [(Alphanum, "a-zA-Z_$", "a-zA-Z0-9_$"), (Symbol, "=<>+\\-*/", "=<>+\\-*/")];
+}
+
+lexer! {
+  struct Defaults;
+}
+
+#[test]
+fn lexes_with_its_rules()
+{
+  let toks:Vec<_> = CLexer::new("while ($x<=10) { s = 'it''s'; x++; } // done").collect();
+  assert_eq!(toks, vec![
+    (Keyword("while"),1,1), (Symbol("("),1,7), (Alphanum("$x"),1,8), (Symbol("<="),1,10),
+    (Num(10),1,12), (Symbol(")"),1,14), (Symbol("{"),1,16), (Alphanum("s"),1,18),
+    (Symbol("="),1,20), (Strlit("'it''s'"),1,22), (Symbol(";"),1,29), (Alphanum("x"),1,31),
+    (Symbol("++"),1,32), (Symbol(";"),1,34), (Symbol("}"),1,36),
+  ]);
+}
+
+#[test]
+fn dereferences_to_table_lexer()
+{
+  let mut lexer = CLexer::new("a /* c */ b");
+  lexer.keep_comment = true;
+  assert_eq!(lexer.next(), Some((Alphanum("a"),1,1)));
+  assert_eq!(lexer.next(), Some((Verbatim("/* c */"),1,3)));
+  assert_eq!(lexer.column(), 10);
+  assert_eq!(lexer.state_name(), "CLexer");
+}
+
+#[test]
+fn defaults_match_str_tokenizer()
+{
+  let input = "x = y+1 /* c */ if (z) \"s\\\"t\" // end\n w >>= 0x10 .5";
+  let table:Vec<_> = Defaults::new(input).collect();
+  let stk = StrTokenizer::from_str(input);
+  let strtok:Vec<_> = stk.collect();
+  assert_eq!(table, strtok);
+}
+
+#[test]
+fn tables_are_static()
+{
+  assert!(std::ptr::eq(CLexer::states(), CLexer::states()));
+  assert_eq!(CLexer::states()[0].keywords.as_ref(), CLexer::KEYWORDS);
+}
+
+fn rejects(singles:&[char], doubles:&[&str], keywords:&[&str], strings:&[StringRule], classes:&[TokenClass]) -> String
+{
+  let (s,d,k,st,c) = (singles.to_vec(),doubles.to_vec(),keywords.to_vec(),strings.to_vec(),classes.to_vec());
+  let hook = std::panic::take_hook();
+  std::panic::set_hook(Box::new(|_| {}));
+  let result = std::panic::catch_unwind(move || check_lexer_rules(&s,&d,&k,"//",("/*","*/"),&st,&c));
+  std::panic::set_hook(hook);
+  match result {
+    Ok(()) => String::new(),
+    Err(e) => e.downcast_ref::<&str>().map(|s|s.to_string())
+               .or_else(|| e.downcast_ref::<String>().cloned()).unwrap_or_default(),
+  }
+}
+
+#[test]
+fn conflicting_rules_are_rejected()
+{
+  let quote = [StringRule{start:"\"", end:"\"", escape:Escape::Backslash}];
+  let alnum = [TokenClass{kind:TokenKind::Alphanum, start:CharSet::new("a-z-"), cont:CharSet::new("a-z")}];
+  assert_eq!(rejects(&[';'],&["=="],&["if"],&quote,&[]), "");
+  for (msg,result) in [
+    ("duplicate single", rejects(&[';',';'],&[],&[],&[],&[])),
+    ("both a single and a double", rejects(&['é'],&["é"],&[],&[],&[])),
+    ("two ascii characters", rejects(&[],&["==="],&[],&[],&[])),
+    ("starts with a comment marker", rejects(&[],&["/*"],&[],&[],&[])),
+    ("starts with a comment marker", rejects(&[],&["//"],&[],&[],&[])),
+    ("starts a comment or a string", rejects(&['"'],&[],&[],&quote,&[])),
+    ("string delimiter", rejects(&[],&["\"a"],&[],&quote,&[])),
+    ("conflicts with the alphanumeric", rejects(&['_'],&[],&[],&[],&[])),
+    ("conflicts with the alphanumeric", rejects(&['-'],&[],&[],&[],&alnum)),
+    ("contain whitespaces", rejects(&[],&["= "],&[],&[],&[])),
+    ("whitespace single", rejects(&['\t'],&[],&[],&[],&[])),
+    ("keywords must be alphanumeric", rejects(&[],&[],&["x-y"],&[],&[])),
+    ("duplicate keyword", rejects(&[],&[],&["if","if"],&[],&[])),
+    ("Alphanum, Symbol or Verbatim", rejects(&[],&[],&[],&[],
+      &[TokenClass{kind:TokenKind::Num, start:CharSet::new("0-9"), cont:CharSet::new("0-9")}])),
+  ] {
+    assert!(result.contains(msg),"expected \"{}\", got \"{}\"",msg,result);
+  }
+  // non-ascii symbols and keywords are accepted
+  assert_eq!(rejects(&['λ'],&[],&["été"],&[],&[]), "");
+}
@@ -0,0 +1,127 @@
+// Tests of lexer specifications and of the table-driven lexers generated
+// from them.  tests/generated/clike_lexer.rs is the output of generate() for
+// tests/generated/clike.lex, checked in so that it is compiled here.
+use std::path::Path;
+use basic_lexer::*;
+use basic_lexer::RawToken::*;
+
+mod clike { include!("generated/clike_lexer.rs"); }
+
+fn spec_path(name:&str) -> String
+{
+  Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/generated").join(name).to_str().unwrap().to_owned()
+}
+
+fn tokens<'t>(lexer:TableLexer<'_,'t>) -> Vec<(RawToken<'t>,usize,usize)> { lexer.collect() }
+
+const PROGRAM:&str = "x = `a ${b} c`;\nif (y<=0x1F) s = 'it''s' + \"q\\\"\"; // done\n@@ 1_000 2.5e3";
+
+#[test]
+fn generated_module_is_up_to_date()
+{
+  let spec = LexSpec::from_file(&spec_path("clike.lex")).unwrap();
+  let mut out = Vec::new();
+  spec.generate(&mut out).unwrap();
+  let checked_in = std::fs::read_to_string(spec_path("clike_lexer.rs")).unwrap();
+  assert_eq!(String::from_utf8(out).unwrap(), checked_in, "regenerate tests/generated/clike_lexer.rs");
+}
+
+#[test]
+fn generated_tables_lex_like_runtime_tables()
+{
+  let spec = LexSpec::from_file(&spec_path("clike.lex")).unwrap();
+  let tables = spec.tables();
+  let mut runtime = TableLexer::new(&tables,PROGRAM);
+  runtime.keep_comment = true;
+  assert_eq!(tokens(clike::make_tokenizer(PROGRAM)), tokens(runtime));
+}
+
+#[test]
+fn states_strings_classes_and_numbers()
+{
+  let mut lexer = clike::make_tokenizer(PROGRAM);
+  let mut seen = Vec::new();
+  while let Some((token,line,column)) = lexer.next_token() { seen.push((token,line,column,lexer.state_name())); }
+  assert_eq!(seen, vec![
+    (Alphanum("x"),1,1,"main"), (Symbol("="),1,3,"main"),
+    (Symbol("`"),1,5,"template"), (Verbatim("a ${b} c"),1,6,"template"), (Symbol("`"),1,14,"main"),
+    (Symbol(";"),1,15,"main"),
+    (Keyword("if"),2,1,"main"), (Symbol("("),2,4,"main"), (Alphanum("y"),2,5,"main"),
+    (Symbol("<="),2,6,"main"), (Num(31),2,8,"main"), (Symbol(")"),2,12,"main"),
+    (Alphanum("s"),2,14,"main"), (Symbol("="),2,16,"main"), (Strlit("'it''s'"),2,18,"main"),
+    (Symbol("+"),2,26,"main"), (Strlit("\"q\\\"\""),2,28,"main"), (Symbol(";"),2,33,"main"),
+    (Verbatim("// done"),2,35,"main"),
+    (Symbol("@@"),3,1,"main"), (Num(1000),3,4,"main"), (Float(2500.0),3,10,"main"),
+  ]);
+}
+
+#[test]
+fn unrecognized_and_unclosed_input_are_errors()
+{
+  let spec = LexSpec::parse("singles ;\nclass alphanum a-z").unwrap();
+  let tables = spec.tables();
+  let mut lexer = TableLexer::new(&tables,"a # b; \"open\nstring");
+  assert_eq!(lexer.next_token(), Some((Alphanum("a"),1,1)));
+  assert_eq!(lexer.next_token(), Some((LexError,1,3)));
+  assert_eq!(lexer.last_error(), "unrecognized symbol");
+  assert_eq!(lexer.next_token(), Some((Alphanum("b"),1,5)));
+  assert_eq!(lexer.next_token(), Some((Symbol(";"),1,6)));
+  assert_eq!(lexer.next_token(), Some((LexError,1,8)));
+  assert_eq!(lexer.last_error(), "unclosed string");
+  assert_eq!(lexer.next_token(), None);
+  assert_eq!(lexer.line(), 2);
+}
+
+#[test]
+fn multiline_tokens_count_lines()
+{
+  let spec = LexSpec::parse("keep newline comment").unwrap();
+  let tables = spec.tables();
+  let mut lexer = TableLexer::new(&tables,"a /* one\ntwo */ b\r\n\"x\ny\" c");
+  lexer.keep_comment = spec.keep_comment;
+  lexer.keep_newline = spec.keep_newline;
+  assert_eq!(tokens(lexer), vec![
+    (Alphanum("a"),1,1), (Verbatim("/* one\ntwo */"),1,3), (Alphanum("b"),2,8), (Newline,2,9),
+    (Strlit("\"x\ny\""),3,1), (Alphanum("c"),4,4),
+  ]);
+}
+
+#[test]
+fn char_sets()
+{
+  let set = CharSet::new(r"a-cx\-é-ë_");
+  for c in ['a','b','c','x','-','é','ê','ë','_'] { assert!(set.contains(c),"{:?}",c); }
+  for c in ['d','w','\\','è','ì',' '] { assert!(!set.contains(c),"{:?}",c); }
+  let trailing = CharSet::new("+-");
+  assert!(trailing.contains('+') && trailing.contains('-') && !trailing.contains(','));
+}
+
+#[test]
+fn initial_state_configures_str_tokenizer()
+{
+  let spec = LexSpec::parse("singles ;\ndoubles ==\nkeywords if\nstring ' ' doubled\nnumbers binary").unwrap();
+  let mut stk = spec.tokenizer("if a=='it''s'; 0b101");
+  stk.set_quiet(true);
+  let toks:Vec<_> = stk.map(|(t,_,_)|t).collect();
+  assert_eq!(toks, vec![Keyword("if"),Alphanum("a"),Symbol("=="),Strlit("'it''s'"),Symbol(";"),Num(5)]);
+}
+
+#[test]
+fn parse_errors_name_the_line()
+{
+  for (text,msg) in [
+    ("singles ab","line 1: \"ab\" is not a single character"),
+    ("\ndoubles ===","line 2: \"===\" is not a two-character symbol"),
+    ("string \"","line 1: string requires a start and an end delimiter"),
+    ("string ' ' twice","line 1: unknown escape \"twice\" in string"),
+    ("class number 0-9","line 1: class requires a kind"),
+    ("numbers roman","line 1: unknown number format \"roman\""),
+    ("state a\nstate a","line 2: state \"a\" is defined twice"),
+    ("goto nowhere {","line 1: unknown state \"nowhere\""),
+    ("frobnicate","line 1: unknown directive \"frobnicate\""),
+  ] {
+    let e = LexSpec::parse(text).unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+    assert!(e.to_string().contains(msg),"{}: {}",text,e);
+  }
+}
@@ -0,0 +1,64 @@
+// Tests of the recognition of \n, \r\n, \r and unicode line terminators
+use basic_lexer::*;
+use basic_lexer::RawToken::*;
+
+fn lines_and_endings(input:&str, unicode:bool) -> Vec<(RawToken<'_>,usize,usize,Option<LineEnding>)>
+{
+  let mut stk = StrTokenizer::from_str(input);
+  stk.keep_newline = true;
+  stk.unicode_newlines = unicode;
+  let mut out = Vec::new();
+  while let Some((t,l,c)) = stk.next() { out.push((t,l,c,stk.line_ending())); }
+  out
+}
+
+#[test]
+fn all_terminators_end_lines()
+{
+  let tokens = lines_and_endings("a\nb\r\nc\rd", false);
+  assert_eq!(tokens, vec![
+    (Alphanum("a"),1,1,None),(Newline,1,2,Some(LineEnding::Lf)),
+    (Alphanum("b"),2,1,Some(LineEnding::Lf)),(Newline,2,2,Some(LineEnding::CrLf)),
+    (Alphanum("c"),3,1,Some(LineEnding::CrLf)),(Newline,3,2,Some(LineEnding::Cr)),
+    (Alphanum("d"),4,1,Some(LineEnding::Cr))]);
+}
+
+#[test]
+fn unicode_terminators_are_optional()
+{
+  let input = "a\u{2028}b\u{2029}c";
+  let tokens:Vec<_> = lines_and_endings(input,false).into_iter().map(|(t,l,_,_)|(t,l)).collect();
+  // without the flag they are only whitespaces
+  assert_eq!(tokens, vec![(Alphanum("a"),1),(Alphanum("b"),1),(Alphanum("c"),1)]);
+  let tokens = lines_and_endings(input,true);
+  assert_eq!(tokens[1], (Newline,1,2,Some(LineEnding::LineSeparator)));
+  assert_eq!(tokens[3], (Newline,2,2,Some(LineEnding::ParagraphSeparator)));
+  assert_eq!(tokens[4], (Alphanum("c"),3,1,Some(LineEnding::ParagraphSeparator)));
+  assert_eq!(LineEnding::CrLf.as_str(), "\r\n");
+}
+
+#[test]
+fn carriage_returns_stay_out_of_tokens()
+{
+  let mut stk = StrTokenizer::from_str("x = \"one\r\ntwo\"\r\n// note\r\ny\r");
+  stk.keep_comment = true;
+  assert_eq!(stk.collect::<Vec<_>>(), vec![(Alphanum("x"),1,1),(Symbol("="),1,3),
+    (Strlit("\"one\r\ntwo\""),1,5),(Verbatim("// note"),3,1),(Alphanum("y"),4,1)]);
+}
+
+#[test]
+fn file_tokenizer_counts_all_terminators()
+{
+  let path = std::env::temp_dir().join("basic_lexer_line_endings.txt");
+  std::fs::write(&path, "a\r\nb\rc\nd").unwrap();
+  let mut ft = File_tokenizer::new(path.to_str().unwrap());
+  ft.set_keep_newline(true);
+  let mut out = Vec::new();
+  while let Some(t) = ft.next() { out.push((t,ft.line_number(),ft.line_ending())); }
+  // lines are read whole, so the terminator of the current line is known
+  assert_eq!(out, vec![
+    (Token::Alphanum("a".to_string()),1,Some(LineEnding::CrLf)),(Token::Newline,1,Some(LineEnding::CrLf)),
+    (Token::Alphanum("b".to_string()),2,Some(LineEnding::Cr)),(Token::Newline,2,Some(LineEnding::Cr)),
+    (Token::Alphanum("c".to_string()),3,Some(LineEnding::Lf)),(Token::Newline,3,Some(LineEnding::Lf)),
+    (Token::Alphanum("d".to_string()),4,None)]);
+}
@@ -0,0 +1,65 @@
+// Tests of peeking at tokens ahead and expecting tokens
+use basic_lexer::*;
+use basic_lexer::RawToken::*;
+
+#[test]
+fn peek_does_not_consume()
+{
+  let mut stk = StrTokenizer::from_str("f(x,\n y)");
+  stk.add_single(',');
+  assert_eq!(stk.peek(0), Some(&(Alphanum("f"),1,1)));
+  assert_eq!(stk.peek(4), Some(&(Alphanum("y"),2,2)));
+  assert_eq!(stk.peek_kind(5), Some(TokenKind::Symbol));
+  assert_eq!(stk.peek(6), None);
+  // the position is that after the last token consumed
+  assert_eq!((stk.line(),stk.column()), (1,1));
+  let all:Vec<_> = stk.collect();
+  assert_eq!(all.len(), 6);
+  assert_eq!(all[4], (Alphanum("y"),2,2));
+}
+
+#[test]
+fn expect_consumes_matching_tokens()
+{
+  let mut stk = StrTokenizer::from_str("let x = 1");
+  stk.add_keywords("let");
+  assert_eq!(stk.expect(TokenKind::Keyword).unwrap(), (Keyword("let"),1,1));
+  assert_eq!(stk.expect(TokenKind::Alphanum).unwrap(), (Alphanum("x"),1,5));
+  let e = stk.expect_symbol("==").unwrap_err();
+  assert_eq!(e.message, "expected \"==\", found \"=\"");
+  assert_eq!((e.start,e.end), (6,7));
+  assert_eq!(stk.expect_symbol("=").unwrap(), (Symbol("="),1,7));
+  assert_eq!(stk.expect(TokenKind::Num).unwrap(), (Num(1),1,9));
+  let e = stk.expect(TokenKind::Num).unwrap_err();
+  assert_eq!(e.message, "expected Num, found end of input");
+}
+
+#[test]
+fn expect_reports_the_error_of_a_bad_token()
+{
+  let mut stk = StrTokenizer::from_str("\"unclosed");
+  stk.set_quiet(true);
+  let e = stk.expect(TokenKind::Alphanum).unwrap_err();
+  assert_eq!(e.message, "expected Alphanum, found \"unclosed string\"");
+  assert_eq!(e.start, 0);
+  // the error is reported once the token is consumed
+  assert_eq!(stk.error_diagnostic(), None);
+  assert_eq!(stk.next(), Some((LexError,1,1)));
+  assert!(stk.error_diagnostic().is_some());
+}
+
+#[test]
+fn file_tokenizer_peeks_and_expects()
+{
+  let path = std::env::temp_dir().join("basic_lexer_lookahead.txt");
+  std::fs::write(&path, "f ( x )\n").unwrap();
+  let mut ft = File_tokenizer::new(path.to_str().unwrap());
+  assert_eq!(ft.peek_kind(0), Some(TokenKind::Alphanum));
+  assert_eq!(ft.peek(1), Some(&Token::Symbol("(".to_string())));
+  assert_eq!(ft.advance(), Some(Token::Alphanum("f".to_string())));
+  assert!(ft.expect(TokenKind::Num).unwrap_err().starts_with("expected Num, found Symbol"));
+  assert_eq!(ft.expect_symbol("("), Ok(Token::Symbol("(".to_string())));
+  assert_eq!(ft.expect(TokenKind::Alphanum), Ok(Token::Alphanum("x".to_string())));
+  assert_eq!(Token::Nothing.kind(), None);
+  assert_eq!(Token::Newline.kind(), Some(TokenKind::Newline));
+}
@@ -0,0 +1,52 @@
+// Tests of memory-mapped sources
+#![cfg(feature="mmap")]
+use basic_lexer::*;
+use basic_lexer::RawToken::*;
+
+// writes bytes to a file in the temporary directory and returns its path
+fn temp_file(name:&str, bytes:&[u8]) -> String
+{
+  let path = std::env::temp_dir().join(name);
+  std::fs::write(&path,bytes).unwrap();
+  path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn mapped_source_has_file_contents()
+{
+  let path = temp_file("basic_lexer_mmap_plain.txt", b"let x = 1;\nlet y = 2;\n");
+  let source = unsafe { LexSource::from_mmap(&path) }.unwrap();
+  assert_eq!(source.get_contents(), "let x = 1;\nlet y = 2;\n");
+  assert_eq!(source.get_encoding(), Encoding::Utf8);
+  assert!(!source.has_bom());
+  assert_eq!(source.line_text(2), Some("let y = 2;"));
+}
+
+#[test]
+fn tokenizer_borrows_from_mapping()
+{
+  let path = temp_file("basic_lexer_mmap_tokens.txt", "\u{FEFF}x = \"é\";".as_bytes());
+  let source = unsafe { LexSource::from_mmap(&path) }.unwrap();
+  assert!(source.has_bom());
+  let contents = source.get_contents().as_bytes().as_ptr_range();
+  let tokens:Vec<_> = StrTokenizer::from_source(&source).collect();
+  assert_eq!(tokens, vec![(Alphanum("x"),1,1),(Symbol("="),1,3),(Strlit("\"é\""),1,5),(Symbol(";"),1,9)]);
+  if let (Alphanum(x),_,_) = tokens[0] { assert!(contents.contains(&x.as_ptr())); }
+}
+
+#[test]
+fn utf16_files_are_decoded()
+{
+  let mut bytes = vec![0xFF,0xFE];
+  for u in "ab".encode_utf16() { bytes.extend(u.to_le_bytes()); }
+  let path = temp_file("basic_lexer_mmap_utf16.txt", &bytes);
+  let source = unsafe { LexSource::from_mmap(&path) }.unwrap();
+  assert_eq!(source.get_contents(), "ab");
+  assert_eq!(source.get_encoding(), Encoding::Utf16Le);
+}
+
+#[test]
+fn missing_file_is_an_error()
+{
+  assert!(unsafe { LexSource::from_mmap("/no/such/file") }.is_err());
+}
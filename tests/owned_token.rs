@@ -0,0 +1,49 @@
+// Tests of OwnedToken and the conversions between token types
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use basic_lexer::*;
+
+#[test]
+fn owned_tokens_outlive_the_input()
+{
+  let tokens:Vec<OwnedToken> = {
+    let input = String::from("x = 'c' + \"s\" 2.5");
+    StrTokenizer::from_str(&input).map(|(t,_,_)| t.into_owned()).collect()
+  };
+  assert_eq!(tokens, vec![OwnedToken::Alphanum("x".to_owned()),OwnedToken::Symbol("=".to_owned()),
+    OwnedToken::Char('c'),OwnedToken::Symbol("+".to_owned()),OwnedToken::Strlit("\"s\"".to_owned()),
+    OwnedToken::Float(2.5)]);
+  assert_eq!(tokens[0].as_raw(), RawToken::Alphanum("x"));
+  assert_eq!(tokens[5].kind(), TokenKind::Float);
+  assert_eq!(OwnedToken::from(RawToken::Num(3)), OwnedToken::Num(3));
+}
+
+#[test]
+fn tokens_are_compared_and_hashed_by_value()
+{
+  let set:HashSet<OwnedToken> = ["a","b","a"].iter().map(|s| OwnedToken::Alphanum(s.to_string())).collect();
+  assert_eq!(set.len(), 2);
+  assert_eq!(OwnedToken::Float(f64::NAN), OwnedToken::Float(f64::NAN));
+  assert_ne!(OwnedToken::Float(0.0), OwnedToken::Float(-0.0));
+  assert_ne!(OwnedToken::Alphanum("k".into()), OwnedToken::Keyword("k".into()));
+  let raw:HashSet<RawToken> = [RawToken::Num(1),RawToken::Num(1),RawToken::Newline].iter().cloned().collect();
+  assert_eq!(raw.len(), 2);
+}
+
+#[test]
+fn legacy_tokens_convert_to_owned_tokens()
+{
+  let pairs = vec![
+    (Token::Integer(7),OwnedToken::Num(7)),
+    (Token::Stringlit("\"s\"".into()),OwnedToken::Strlit("\"s\"".into())),
+    (Token::Keyword("if".into()),OwnedToken::Keyword("if".into())),
+    (Token::Newline,OwnedToken::Newline),
+  ];
+  for (legacy,owned) in pairs {
+    assert_eq!(legacy.as_raw(), Some(owned.as_raw()));
+    assert_eq!(legacy.kind(), Some(owned.kind()));
+    assert_eq!(OwnedToken::try_from(legacy), Ok(owned));
+  }
+  assert_eq!(OwnedToken::try_from(Token::Nothing), Err(Token::Nothing));
+  assert_eq!(Token::Nothing.as_raw(), None);
+}
@@ -0,0 +1,71 @@
+// Tests of BatchTokenizer, which tokenizes many sources on a thread pool
+use basic_lexer::*;
+use basic_lexer::RawToken::*;
+
+fn def() -> LexerDef<'static>
+{
+  let mut def = LexerDef::new();
+  def.add_single(';');
+  def.add_double("==");
+  def
+}
+
+#[test]
+fn results_are_in_input_order()
+{
+  let def = def();
+  let inputs:Vec<String> = (0..50).map(|i| format!("x{} == {};",i,i)).collect();
+  let strs:Vec<&str> = inputs.iter().map(|s|s.as_str()).collect();
+  for threads in [1,3,8] {
+    let mut batch = BatchTokenizer::new(&def);
+    batch.set_threads(threads);
+    let results = batch.tokenize_strs(&strs);
+    assert_eq!(results.len(), 50);
+    for (i,result) in results.iter().enumerate() {
+      let expected:Vec<_> = def.tokenizer(strs[i]).collect();
+      assert_eq!(result.tokens, expected);
+      assert!(result.errors.is_empty());
+    }
+  }
+}
+
+#[test]
+fn errors_are_diagnostics()
+{
+  let def = def();
+  let batch = BatchTokenizer::new(&def);
+  let results = batch.tokenize_strs(&["a;", "b = \"open"]);
+  assert!(results[0].errors.is_empty());
+  assert_eq!(results[1].tokens.last(), Some(&(LexError,1,5)));
+  assert_eq!(results[1].errors.len(), 1);
+  let error = &results[1].errors[0];
+  assert_eq!(error.severity, Severity::Error);
+  assert!(!error.message.is_empty());
+  assert_eq!(error.start, 4);
+}
+
+#[test]
+fn sources_keep_whitespace_and_comments()
+{
+  let def = def();
+  let mut batch = BatchTokenizer::new(&def);
+  batch.keep_whitespace = true;
+  batch.keep_comment = true;
+  let sources = vec![LexSource::from_string("a.c","a; // note".to_string())];
+  let results = batch.tokenize_sources(&sources);
+  let tokens:Vec<&RawToken> = results[0].tokens.iter().map(|(t,_,_)|t).collect();
+  assert_eq!(tokens, vec![&Alphanum("a"),&Symbol(";"),&Whitespace(1),&Verbatim("// note")]);
+}
+
+#[test]
+fn load_reports_missing_files()
+{
+  let path = std::env::temp_dir().join("basic_lexer_parallel_load.txt");
+  std::fs::write(&path, "y == 2;").unwrap();
+  let path = path.to_str().unwrap().to_string();
+  let def = def();
+  let batch = BatchTokenizer::new(&def);
+  let loaded = batch.load(&[path.as_str(), "/no/such/file"]);
+  assert_eq!(loaded[0].as_ref().unwrap().get_contents(), "y == 2;");
+  assert!(loaded[1].is_err());
+}
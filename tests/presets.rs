@@ -0,0 +1,152 @@
+// Golden tests of the language presets: each sample file in tests/golden is
+// tokenized with the preset for its extension, and the tokens are compared
+// with those recorded in the .tokens file next to it.  Run the tests with
+// UPDATE_GOLDEN=1 to record the tokens again after an intended change.
+use std::fmt::Write;
+use std::path::Path;
+use basic_lexer::*;
+
+fn render(lang:Language, text:&str) -> String
+{
+  let mut stk = StrTokenizer::for_language(lang);
+  stk.set_input(text);
+  stk.keep_comment = true;
+  let mut out = String::new();
+  while let Some((token,line,column)) = stk.next_token() {
+    let _ = writeln!(out,"{}:{} {:?}",line,column,token);
+  }
+  out
+}
+
+fn check(sample:&str)
+{
+  let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(sample);
+  let lang = Language::from_path(sample).expect("no language for sample");
+  let text = std::fs::read_to_string(&path).unwrap();
+  let actual = render(lang,&text);
+  let golden = path.with_extension(format!("{}.tokens",path.extension().unwrap().to_str().unwrap()));
+  if std::env::var_os("UPDATE_GOLDEN").is_some() {
+    std::fs::write(&golden,&actual).unwrap();
+    return;
+  }
+  let expected = std::fs::read_to_string(&golden)
+    .unwrap_or_else(|_| panic!("missing {}, run with UPDATE_GOLDEN=1",golden.display()));
+  if actual!=expected {
+    let (i,(a,e)) = actual.lines().zip(expected.lines()).enumerate()
+      .find(|(_,(a,e))| a!=e)
+      .unwrap_or((actual.lines().count().min(expected.lines().count()),("<end>","<end>")));
+    panic!("{}: token {} differs from {}:\n  actual:   {}\n  expected: {}",sample,i+1,golden.display(),a,e);
+  }
+}
+
+#[test]
+fn c_preset() { check("sample.c"); }
+
+#[test]
+fn rust_preset() { check("sample.rs"); }
+
+#[test]
+fn python_preset() { check("sample.py"); }
+
+#[test]
+fn json_preset() { check("sample.json"); }
+
+#[test]
+fn toml_preset() { check("sample.toml"); }
+
+#[test]
+fn sql_preset() { check("sample.sql"); }
+
+#[test]
+fn lisp_preset() { check("sample.lisp"); }
+
+#[test]
+fn javascript_preset() { check("sample.js"); }
+
+#[test]
+fn language_names()
+{
+  for lang in Language::ALL {
+    assert_eq!(Language::from_name(lang.name()),Some(lang));
+  }
+  assert_eq!(Language::from_path("src/lib.rs"),Some(Language::Rust));
+  assert_eq!(Language::from_path("Makefile"),None);
+}
+
+fn tokens(lang:Language, text:&str) -> Vec<RawToken<'_>>
+{
+  let mut stk = StrTokenizer::for_language(lang);
+  stk.set_input(text);
+  stk.map(|(t,_,_)|t).collect()
+}
+
+#[test]
+fn definition_and_tokenizer_agree()
+{
+  let text = "let x = 0x1F_u8; /* a /* b */ c */ y";
+  let def = LexerDef::for_language(Language::Rust);
+  let from_def:Vec<_> = def.tokenizer(text).map(|(t,_,_)|t).collect();
+  assert_eq!(from_def, tokens(Language::Rust,text));
+  assert!(!Language::C.significant_layout());
+  let stk = StrTokenizer::for_language(Language::Python);
+  assert!(stk.keep_newline && stk.keep_whitespace);
+  let stk = StrTokenizer::for_language(Language::C);
+  assert!(!stk.keep_newline && !stk.keep_whitespace);
+}
+
+#[test]
+fn c_comments_and_operators()
+{
+  use RawToken::*;
+  assert_eq!(tokens(Language::C,"a->b += 1e3; // c\nx /* y */ 'z'"),
+    vec![Alphanum("a"),Symbol("->"),Alphanum("b"),Symbol("+="),Float(1000.0),Symbol(";"),
+         Alphanum("x"),Char('z')]);
+  assert_eq!(tokens(Language::C,"while sizeof"), vec![Keyword("while"),Keyword("sizeof")]);
+}
+
+#[test]
+fn rust_nested_comments_and_numbers()
+{
+  use RawToken::*;
+  assert_eq!(tokens(Language::Rust,"a::b /* x /* y */ z */ 0b1010 1_000 0o17"),
+    vec![Alphanum("a"),Symbol("::"),Alphanum("b"),Num(10),Num(1000),Num(15)]);
+}
+
+#[test]
+fn python_layout_and_strings()
+{
+  use RawToken::*;
+  let mut stk = StrTokenizer::for_language(Language::Python);
+  stk.set_input("if x:\n    s = '''a\n'b'''  # c");
+  let toks:Vec<_> = stk.map(|(t,_,_)|t).collect();
+  assert_eq!(toks, vec![Keyword("if"),Whitespace(1),Alphanum("x"),Symbol(":"),Newline,
+    Whitespace(4),Alphanum("s"),Whitespace(1),Symbol("="),Whitespace(1),Strlit("'''a\n'b'''"),Whitespace(2)]);
+}
+
+#[test]
+fn json_has_no_comments()
+{
+  use RawToken::*;
+  assert_eq!(tokens(Language::Json,"{\"a\": [-1, 2.5e1, true, null]}"),
+    vec![Symbol("{"),Strlit("\"a\""),Symbol(":"),Symbol("["),Symbol("-"),Num(1),Symbol(","),
+         Float(25.0),Symbol(","),Keyword("true"),Symbol(","),Keyword("null"),Symbol("]"),Symbol("}")]);
+  assert_eq!(tokens(Language::Json,"1 // 2"), vec![Num(1),Symbol("//"),Num(2)]);
+}
+
+#[test]
+fn toml_literal_strings_and_comments()
+{
+  use RawToken::*;
+  assert_eq!(tokens(Language::Toml,"a.b = 'c:\\d' # e\nf = \"\"\"g\"\"\""),
+    vec![Alphanum("a"),Symbol("."),Alphanum("b"),Symbol("="),Strlit("'c:\\d'"),
+         Alphanum("f"),Symbol("="),Strlit("\"\"\"g\"\"\"")]);
+}
+
+#[test]
+fn sql_keywords_ignore_case()
+{
+  use RawToken::*;
+  assert_eq!(tokens(Language::Sql,"SELECT name FROM t WHERE s <> 'it''s' -- c"),
+    vec![Keyword("SELECT"),Alphanum("name"),Keyword("FROM"),Alphanum("t"),Keyword("WHERE"),
+         Alphanum("s"),Symbol("<>"),Strlit("'it''s'")]);
+}
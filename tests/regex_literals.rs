@@ -0,0 +1,71 @@
+// Tests of regular expression literals and the division symbol
+use basic_lexer::*;
+use basic_lexer::RawToken::*;
+
+fn lex<'t>(stk:&mut StrTokenizer<'t>, input:&'t str) -> Vec<RawToken<'t>>
+{
+  stk.set_input(input);
+  stk.set_quiet(true);
+  stk.map(|(t,_,_)|t).collect()
+}
+
+fn js() -> StrTokenizer<'static> { StrTokenizer::for_language(Language::JavaScript) }
+
+#[test]
+fn regex_after_operators_and_division_after_operands()
+{
+  let mut stk = js();
+  assert_eq!(lex(&mut stk,"x = /a+b/gi.test(s) / 2"),
+    vec![Alphanum("x"),Symbol("="),RegexLit("a+b","gi"),Symbol("."),Alphanum("test"),Symbol("("),
+         Alphanum("s"),Symbol(")"),Symbol("/"),Num(2)]);
+  assert_eq!(lex(&mut stk,"/^x/.exec(a[0] / b)"),
+    vec![RegexLit("^x",""),Symbol("."),Alphanum("exec"),Symbol("("),Alphanum("a"),Symbol("["),Num(0),
+         Symbol("]"),Symbol("/"),Alphanum("b"),Symbol(")")]);
+  assert_eq!(lex(&mut stk,"return /[/]\\/x/; f(/y/)"),
+    vec![Keyword("return"),RegexLit("[/]\\/x",""),Symbol(";"),Alphanum("f"),Symbol("("),RegexLit("y",""),Symbol(")")]);
+}
+
+#[test]
+fn postfix_increments_end_operands()
+{
+  let mut stk = js();
+  assert_eq!(lex(&mut stk,"a = b++ / c / d"),
+    vec![Alphanum("a"),Symbol("="),Alphanum("b"),Symbol("++"),Symbol("/"),Alphanum("c"),
+         Symbol("/"),Alphanum("d")]);
+  assert_eq!(lex(&mut stk,"n-- / 2 /x/"),
+    vec![Alphanum("n"),Symbol("--"),Symbol("/"),Num(2),Symbol("/"),Alphanum("x"),Symbol("/")]);
+}
+
+#[test]
+fn operand_keywords_are_configurable()
+{
+  let mut stk = js();
+  assert_eq!(lex(&mut stk,"this / 2 / 3"), vec![Keyword("this"),Symbol("/"),Num(2),Symbol("/"),Num(3)]);
+  assert_eq!(lex(&mut stk,"typeof /x/"), vec![Keyword("typeof"),RegexLit("x","")]);
+  stk.add_keywords("self");
+  stk.set_operand_keywords("self this");
+  assert_eq!(lex(&mut stk,"self / 2 / 3"), vec![Keyword("self"),Symbol("/"),Num(2),Symbol("/"),Num(3)]);
+  // null is no longer an operand
+  assert_eq!(lex(&mut stk,"null /x/"), vec![Keyword("null"),RegexLit("x","")]);
+  let before = js().config_hash();
+  assert_ne!(stk.config_hash(), before);
+}
+
+#[test]
+fn comments_and_unclosed_regex()
+{
+  let mut stk = js();
+  stk.keep_comment = true;
+  assert_eq!(lex(&mut stk,"= // c\n= /* d */ /e/"),
+    vec![Symbol("="),Verbatim("// c"),Symbol("="),Verbatim("/* d */"),RegexLit("e","")]);
+  let mut stk = js();
+  assert_eq!(lex(&mut stk,"= /ab\nc"), vec![Symbol("="),LexError,Alphanum("c")]);
+  assert_eq!(stk.error_diagnostic().unwrap().message, "unclosed regular expression");
+}
+
+#[test]
+fn regex_literals_are_off_by_default()
+{
+  let mut stk = StrTokenizer::new();
+  assert_eq!(lex(&mut stk,"= /a/"), vec![Symbol("="),Symbol("/"),Alphanum("a"),Symbol("/")]);
+}
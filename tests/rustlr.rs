@@ -0,0 +1,47 @@
+// Tests of the rustlr adapter, run with --features rustlr
+#![cfg(feature = "rustlr")]
+use basic_lexer::*;
+use rustlr::Tokenizer;
+
+fn translate(tok:RawToken<'_>) -> Option<(&str,i64)>
+{
+  match tok {
+    RawToken::Num(n) => Some(("num",n)),
+    RawToken::Alphanum(_) => Some(("ID",0)),
+    RawToken::Symbol(s) => Some((s,0)),
+    _ => None,
+  }
+}
+
+#[test]
+fn terminals_carry_values_and_positions()
+{
+  let mut stk = StrTokenizer::from_str("x = 12;\n  // comment\n  y + 3");
+  stk.add_single(';');
+  let mut lexer = RustlrTokenizer::new(stk,translate);
+  let mut syms = Vec::new();
+  while let Some(t) = lexer.nextsym() { syms.push((t.sym,t.value,t.line,t.column)); }
+  assert_eq!(syms, vec![("ID",0,1,1),("=",0,1,3),("num",12,1,5),(";",0,1,7),
+                        ("ID",0,3,3),("+",0,3,5),("num",3,3,7)]);
+}
+
+#[test]
+fn get_line_follows_tokenizer_line_breaks()
+{
+  let lexer = RustlrTokenizer::new(StrTokenizer::from_str("one\r\ntwo\rthree\nfour"),translate);
+  assert_eq!(lexer.get_line(0), None);
+  assert_eq!(lexer.get_line(1), Some("one"));
+  assert_eq!(lexer.get_line(2), Some("two"));
+  assert_eq!(lexer.get_line(3), Some("three"));
+  assert_eq!(lexer.get_line(4), Some("four"));
+  assert_eq!(lexer.get_line(5), None);
+}
+
+#[test]
+fn get_line_agrees_with_current_line()
+{
+  let mut lexer = RustlrTokenizer::new(StrTokenizer::from_str("a\rb\r\nc d"),translate);
+  while let Some(t) = lexer.nextsym() {
+    assert_eq!(lexer.get_line(t.line), Some(lexer.current_line()));
+  }
+}
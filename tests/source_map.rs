@@ -0,0 +1,61 @@
+// Tests of SourceMap, which gives global byte offsets to many sources
+use basic_lexer::*;
+use basic_lexer::RawToken::*;
+
+fn two_sources() -> SourceMap<'static>
+{
+  let mut map = SourceMap::new();
+  map.add(LexSource::from_string("a.c","int a;\nint b;".to_string()));
+  map.add(LexSource::from_string("<prelude>","x y".to_string()));
+  map
+}
+
+#[test]
+fn sources_get_disjoint_ranges()
+{
+  let map = two_sources();
+  assert_eq!(map.len(), 2);
+  assert_eq!(map.base(0), Some(0));
+  assert_eq!(map.base(1), Some(14));
+  assert_eq!(map.base(2), None);
+  assert_eq!(map.get(1).map(|s|s.get_id()), Some(1));
+  assert_eq!(map.lookup(13), Some(0));
+  assert_eq!(map.lookup(14), Some(1));
+  assert_eq!(map.lookup(17), Some(1));
+  assert_eq!(map.lookup(18), None);
+  assert_eq!(map.local(16), Some((1,2)));
+}
+
+#[test]
+fn offsets_resolve_to_paths_lines_and_columns()
+{
+  let map = two_sources();
+  assert_eq!(map.resolve(0), Some(("a.c",1,1)));
+  assert_eq!(map.resolve(11), Some(("a.c",2,5)));
+  assert_eq!(map.resolve(map.global_offset(1,2)), Some(("<prelude>",1,3)));
+}
+
+#[test]
+fn spans_give_source_text()
+{
+  let map = two_sources();
+  let a = map.span(0,4,5);
+  let b = map.span(0,11,12);
+  assert_eq!(map.span_text(a), Some("a"));
+  assert_eq!(map.span_text(a.to(b)), Some("a;\nint b"));
+  assert_eq!(a.to(b).len(), 8);
+  assert!(a<b);
+  // a span across two sources has no text
+  assert_eq!(map.span_text(Span::new(12,16)), None);
+}
+
+#[test]
+fn tokenizers_of_the_map_carry_source_ids()
+{
+  let map = two_sources();
+  let mut stk = map.tokenizer(1);
+  assert_eq!(stk.get_source_id(), 1);
+  assert_eq!(stk.next(), Some((Alphanum("x"),1,1)));
+  let offset = map.global_offset(1,stk.position());
+  assert_eq!(map.resolve(offset), Some(("<prelude>",1,2)));
+}
@@ -0,0 +1,71 @@
+// Regression tests of StrTokenizer on input that is not covered by the
+// language presets
+use basic_lexer::*;
+use basic_lexer::RawToken::*;
+
+fn lex(input:&str) -> Vec<(RawToken<'_>,usize,usize)>
+{
+  let mut stk = StrTokenizer::from_str(input);
+  stk.add_double("==");
+  stk.add_single('→');
+  stk.collect()
+}
+
+#[test]
+fn non_ascii_next_to_symbols()
+{
+  assert_eq!(lex("a=é"), vec![(Alphanum("a"),1,1),(Symbol("="),1,2),(LexError,1,3)]);
+  assert_eq!(lex("x==é"), vec![(Alphanum("x"),1,1),(Symbol("=="),1,2),(LexError,1,4)]);
+  assert_eq!(lex("(é)"), vec![(Symbol("("),1,1),(LexError,1,2)]);
+  assert_eq!(lex("a→b"), vec![(Alphanum("a"),1,1),(Symbol("→"),1,2),(Alphanum("b"),1,5)]);
+}
+
+// columns are byte offsets into the line
+#[test]
+fn non_ascii_char_literals_and_strings()
+{
+  assert_eq!(lex("'é' 'λ'"), vec![(Char('é'),1,1),(Char('λ'),1,6)]);
+  assert_eq!(lex("\"héllo wörld\" → x"),
+             vec![(Strlit("\"héllo wörld\""),1,1),(Symbol("→"),1,17),(Alphanum("x"),1,21)]);
+}
+
+#[test]
+fn unclosed_string_column()
+{
+  assert_eq!(lex("x = \"unclosed"), vec![(Alphanum("x"),1,1),(Symbol("="),1,3),(LexError,1,5)]);
+  assert_eq!(lex("  y\n  z = \"open\nstring"),
+             vec![(Alphanum("y"),1,3),(Alphanum("z"),2,3),(Symbol("="),2,5),(LexError,2,7)]);
+}
+
+// hexadecimal numbers were reported two columns to the right of their start
+#[test]
+fn hexadecimal_number_columns()
+{
+  assert_eq!(lex("x 0x1F 0xff"), vec![(Alphanum("x"),1,1),(Num(31),1,3),(Num(255),1,8)]);
+}
+
+// whitespaces of more than one byte were counted as one byte
+#[test]
+fn multibyte_whitespaces_are_skipped_whole()
+{
+  let mut stk = StrTokenizer::from_str("x\u{3000}\u{2003}y = 1");
+  assert_eq!(stk.by_ref().collect::<Vec<_>>(),
+             vec![(Alphanum("x"),1,1),(Alphanum("y"),1,8),(Symbol("="),1,10),(Num(1),1,12)]);
+  stk.set_input("a\u{00A0}b");
+  stk.keep_whitespace = true;
+  assert_eq!(stk.collect::<Vec<_>>(), vec![(Alphanum("a"),1,1),(Whitespace(2),1,2),(Alphanum("b"),1,4)]);
+}
+
+// lines inside unclosed comments, unrecognized input and char literals
+// were not counted
+#[test]
+fn lines_are_counted_to_the_end_of_errors()
+{
+  for (input,line,column) in [("a /* open\n\nmore",3,5),("a é\n\nb",3,2)].iter().copied() {
+    let mut stk = StrTokenizer::from_str(input);
+    stk.set_quiet(true);
+    assert_eq!(stk.by_ref().last(), Some((LexError,1,3)));
+    assert_eq!((stk.line(),stk.column()), (line,column));
+  }
+  assert_eq!(lex("x = '\n' y"), vec![(Alphanum("x"),1,1),(Symbol("="),1,3),(Char('\n'),1,5),(Alphanum("y"),2,3)]);
+}
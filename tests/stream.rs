@@ -0,0 +1,136 @@
+// Tests of StreamTokenizer, which reads its input through a bounded buffer
+use std::io::Cursor;
+use basic_lexer::*;
+
+const SAMPLE:&str = "/* header\n   comment */\nlet x == 3.14159e10 + 0x1F;\n\
+  if (y != -2.5e-3) { c = 'é'; d = '\\n'; }\n\
+  s = \"a string with\\\" quotes\" // trailing\n\
+  \tlong_identifier_name_here <= 12345678901234 >= 0.000001;\n";
+
+fn def() -> LexerDef<'static>
+{
+  let mut def = LexerDef::new();
+  for d in ["==","!=","<=",">="] { def.add_double(d); }
+  for c in ";=+-<>".chars() { def.add_single(c); }
+  def.add_keywords("let if");
+  def
+}
+
+// tokens of the whole input in memory, as owned tokens
+fn expected(def:&LexerDef, input:&str, keep:bool) -> Vec<(OwnedToken,usize,usize)>
+{
+  let mut stk = def.tokenizer(input);
+  stk.keep_whitespace = keep;
+  stk.keep_newline = keep;
+  stk.keep_comment = keep;
+  stk.map(|(t,l,c)| (t.into_owned(),l,c)).collect()
+}
+
+fn streamed(def:&LexerDef, input:&str, keep:bool, capacity:usize) -> Vec<(OwnedToken,usize,usize)>
+{
+  let mut stream = StreamTokenizer::with_capacity(def,Cursor::new(input.as_bytes()),capacity);
+  stream.keep_whitespace = keep;
+  stream.keep_newline = keep;
+  stream.keep_comment = keep;
+  stream.set_quiet(true);
+  stream.collect()
+}
+
+#[test]
+fn stream_matches_str_tokenizer_for_all_buffer_sizes()
+{
+  let def = def();
+  for keep in [false,true] {
+    let expected = expected(&def,SAMPLE,keep);
+    // the longest token of the sample has 25 bytes
+    for capacity in 32..=160 {
+      assert_eq!(streamed(&def,SAMPLE,keep,capacity), expected, "capacity {}", capacity);
+    }
+  }
+}
+
+#[test]
+fn numbers_split_at_buffer_edge()
+{
+  let def = def();
+  let input = "xxxxxxxxxx 3.14159e10 == 2.5;\n";
+  let expected = expected(&def,input,false);
+  for capacity in 16..=40 {
+    assert_eq!(streamed(&def,input,false,capacity), expected, "capacity {}", capacity);
+  }
+}
+
+#[test]
+fn invalid_utf8_ends_stream()
+{
+  let def = def();
+  let mut stream = StreamTokenizer::new(&def,Cursor::new(b"ab \xff\xfe".to_vec()));
+  stream.set_quiet(true);
+  assert_eq!(stream.next(), Some((OwnedToken::Alphanum("ab".to_owned()),1,1)));
+  assert_eq!(stream.next(), Some((OwnedToken::LexError,1,4)));
+  assert_eq!(stream.next(), None);
+  assert_eq!(stream.take_io_error().map(|e|e.kind()), Some(std::io::ErrorKind::InvalidData));
+  // the tokens before the invalid byte are returned, whatever the buffer size
+  let input = b"ab cd\n  ef \xff gh".to_vec();
+  for capacity in 16..=24 {
+    let mut stream = StreamTokenizer::with_capacity(&def,Cursor::new(input.clone()),capacity);
+    stream.set_quiet(true);
+    let tokens:Vec<_> = stream.by_ref().collect();
+    assert_eq!(tokens, vec![(OwnedToken::Alphanum("ab".to_owned()),1,1),(OwnedToken::Alphanum("cd".to_owned()),1,4),
+                            (OwnedToken::Alphanum("ef".to_owned()),2,3),(OwnedToken::LexError,2,6)]);
+    assert!(stream.take_io_error().is_some());
+  }
+  // an incomplete character at the end of the stream
+  let mut stream = StreamTokenizer::new(&def,Cursor::new(b"x \xc3".to_vec()));
+  stream.set_quiet(true);
+  assert_eq!(stream.by_ref().collect::<Vec<_>>(),
+             vec![(OwnedToken::Alphanum("x".to_owned()),1,1),(OwnedToken::LexError,1,3)]);
+}
+
+#[test]
+fn verbatim_tokens_of_hooks_are_not_comments()
+{
+  let mut def = def();
+  def.add_hook("```", |cx| {
+    let rest = cx.rest();
+    let len = rest[3..].find("```").map_or(rest.len(),|i|i+6);
+    Some((RawToken::Verbatim(&rest[..len]),len))
+  });
+  let input = "x ```code``` y // c\nz";
+  let expected = expected(&def,input,false);
+  assert_eq!(expected[1].0, OwnedToken::Verbatim("```code```".to_owned()));
+  for capacity in 16..=32 {
+    assert_eq!(streamed(&def,input,false,capacity), expected, "capacity {}", capacity);
+  }
+}
+
+#[test]
+fn long_prefixes_split_at_buffer_edge()
+{
+  let mut def = def();
+  def.add_reader_macros("# #@@@@@@@@@@");
+  def.set_line_comment("<<<<<<<<<<<<");
+  let input = "x #@@@@@@@@@@ y # z <<<<<<<<<<<< comment\nw ######## #@@@@@@@@@@\n";
+  let expected = expected(&def,input,false);
+  // the longest token is the comment, of 20 bytes
+  for capacity in 24..=48 {
+    assert_eq!(streamed(&def,input,false,capacity), expected, "capacity {}", capacity);
+  }
+}
+
+#[test]
+fn comments_longer_than_the_buffer_are_errors()
+{
+  let def = LexerDef::for_language(Language::Sql);
+  let input = "a --('é'0x1F-+ée10bc and more\nb";
+  for keep in [false,true] {
+    let mut stream = StreamTokenizer::with_capacity(&def,Cursor::new(input.as_bytes()),16);
+    stream.keep_comment = keep;
+    stream.set_quiet(true);
+    assert_eq!(stream.collect::<Vec<_>>(),
+               vec![(OwnedToken::Alphanum("a".to_owned()),1,1),(OwnedToken::LexError,1,3),
+                    (OwnedToken::Alphanum("b".to_owned()),2,1)]);
+  }
+  // a comment that fits in the buffer is not an error
+  assert_eq!(streamed(&def,input,true,64), expected(&def,input,true));
+}
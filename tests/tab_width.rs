@@ -0,0 +1,53 @@
+// Tests of tab stops, visual columns and indentation
+use basic_lexer::*;
+use basic_lexer::RawToken::*;
+
+#[test]
+fn columns_are_bytes_without_tab_width()
+{
+  let mut stk = StrTokenizer::from_str("\tx\t= 1");
+  assert_eq!(stk.tab_width(), 0);
+  assert_eq!(stk.by_ref().collect::<Vec<_>>(), vec![(Alphanum("x"),1,2),(Symbol("="),1,4),(Num(1),1,6)]);
+}
+
+#[test]
+fn columns_are_visual_with_tab_width()
+{
+  let mut stk = StrTokenizer::from_str("\tx\t= 1\n  \ty é");
+  stk.set_quiet(true);
+  stk.set_tab_width(4);
+  assert_eq!(stk.by_ref().collect::<Vec<_>>(), vec![
+    (Alphanum("x"),1,5),(Symbol("="),1,9),(Num(1),1,11),
+    (Alphanum("y"),2,5),(LexError,2,7)]);
+  stk.set_input("ab\tc");
+  stk.set_tab_width(8);
+  assert_eq!(stk.collect::<Vec<_>>(), vec![(Alphanum("ab"),1,1),(Alphanum("c"),1,9)]);
+}
+
+// whitespaces that span lines have the width of their last line
+#[test]
+fn whitespace_text_has_visual_width()
+{
+  let mut stk = StrTokenizer::from_str("x \t y\n\t\tz");
+  stk.set_tab_width(4);
+  stk.keep_whitespace = true;
+  stk.whitespace_text = true;
+  assert_eq!(stk.collect::<Vec<_>>(), vec![
+    (Alphanum("x"),1,1),(WhitespaceText(" \t ",4),1,2),(Alphanum("y"),1,6),
+    (WhitespaceText("\n\t\t",8),2,1),(Alphanum("z"),2,9)]);
+}
+
+#[test]
+fn mixed_indentation_is_an_error()
+{
+  let input = "a\n \tb\n\t\tc\n    d \t e";
+  let mut stk = StrTokenizer::from_str(input);
+  stk.set_quiet(true);
+  stk.mixed_indent_error = true;
+  let tokens:Vec<_> = stk.by_ref().collect();
+  assert_eq!(tokens, vec![(Alphanum("a"),1,1),(LexError,2,1),(Alphanum("b"),2,3),
+                          (Alphanum("c"),3,3),(Alphanum("d"),4,5),(Alphanum("e"),4,9)]);
+  stk.set_input(input);
+  stk.mixed_indent_error = false;
+  assert!(stk.all(|(t,_,_)| t!=LexError));
+}
@@ -0,0 +1,107 @@
+// Tests of TokenStream, the binary cache of the tokens of a text
+use basic_lexer::*;
+
+const TEXT:&str = "int x = 10; // count\nif (x == 2.5) { s = \"a\\\"b\"; c = 'z'; }";
+
+fn tokenizer(text:&str) -> StrTokenizer<'_>
+{
+  let mut stk = StrTokenizer::from_str(text);
+  stk.add_double("==");
+  stk.add_keywords("int if");
+  stk
+}
+
+#[test]
+fn binary_round_trip()
+{
+  let stream = TokenStream::from_tokenizer(&mut tokenizer(TEXT));
+  assert!(stream.tokens.len() > 10);
+  let bytes = stream.to_bytes();
+  assert_eq!(TokenStream::from_bytes(&bytes).unwrap(), stream);
+  let mut read = &bytes[..];
+  assert_eq!(TokenStream::read_from(&mut read).unwrap(), stream);
+  let first = &stream.tokens[0];
+  assert_eq!(first.token, OwnedToken::Keyword("int".to_owned()));
+  assert_eq!((first.line,first.column,first.start,first.end), (1,1,0,3));
+}
+
+#[test]
+fn stream_is_valid_only_for_same_text_and_rules()
+{
+  let stream = TokenStream::from_tokenizer(&mut tokenizer(TEXT));
+  assert!(stream.is_valid_for(&tokenizer(TEXT)));
+  // trailing whitespaces are ignored
+  let padded = format!("{}  \n\n",TEXT);
+  assert!(stream.is_valid_for(&tokenizer(&padded)));
+  assert!(!stream.is_valid_for(&tokenizer("int y = 10;")));
+  // same text, different rules or settings
+  let mut other = tokenizer(TEXT);
+  other.add_double("<=");
+  assert!(!stream.is_valid_for(&other));
+  let mut other = tokenizer(TEXT);
+  other.add_keywords("float");
+  assert!(!stream.is_valid_for(&other));
+  let mut other = tokenizer(TEXT);
+  other.keep_comment = true;
+  assert!(!stream.is_valid_for(&other));
+  let mut other = tokenizer(TEXT);
+  other.set_line_comment("#");
+  assert!(!stream.is_valid_for(&other));
+}
+
+#[test]
+fn config_hash_ignores_order_of_rules()
+{
+  let mut a = StrTokenizer::from_str("");
+  a.add_double("=="); a.add_double("<="); a.add_keywords("if else");
+  let mut b = StrTokenizer::from_str("");
+  b.add_keywords("else if"); b.add_double("<="); b.add_double("==");
+  assert_eq!(a.config_hash(), b.config_hash());
+  assert_eq!(a.get_def().config_hash(), b.get_def().config_hash());
+}
+
+#[test]
+fn load_rejects_stale_files()
+{
+  let path = std::env::temp_dir().join(format!("basic_lexer_cache_{}.tokens",std::process::id()));
+  let stream = TokenStream::from_tokenizer(&mut tokenizer(TEXT));
+  stream.save(&path).unwrap();
+  assert_eq!(TokenStream::load(&path,&tokenizer(TEXT)), Some(stream));
+  let mut other = tokenizer(TEXT);
+  other.add_double("<=");
+  assert_eq!(TokenStream::load(&path,&other), None);
+  assert_eq!(TokenStream::load(&path,&tokenizer("x")), None);
+  std::fs::remove_file(&path).unwrap();
+  assert_eq!(TokenStream::load(&path,&tokenizer(TEXT)), None);
+}
+
+#[test]
+fn malformed_bytes_are_rejected()
+{
+  let bytes = TokenStream::from_tokenizer(&mut tokenizer(TEXT)).to_bytes();
+  let mut wrong_version = bytes.clone();
+  wrong_version[4] = 1;
+  assert!(TokenStream::from_bytes(&wrong_version).is_err());
+  assert!(TokenStream::from_bytes(b"XXXX").is_err());
+  assert!(TokenStream::from_bytes(&bytes[..bytes.len()-1]).is_err());
+  let mut trailing = bytes.clone();
+  trailing.push(0);
+  let err = TokenStream::from_bytes(&trailing).unwrap_err();
+  assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip()
+{
+  let stream = TokenStream::from_tokenizer(&mut tokenizer(TEXT));
+  let json = serde_json::to_string(&stream).unwrap();
+  let back:TokenStream = serde_json::from_str(&json).unwrap();
+  assert_eq!(back, stream);
+  // the message of a legacy error token is kept
+  let tokens = vec![Token::Integer(1), Token::Error("unclosed string")];
+  let json = serde_json::to_string(&tokens).unwrap();
+  assert!(json.contains("unclosed string"));
+  let back:Vec<Token> = serde_json::from_str(&json).unwrap();
+  assert_eq!(back, tokens);
+}
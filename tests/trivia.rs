@@ -0,0 +1,57 @@
+// Tests of LosslessTokenizer, which keeps the exact text of the trivia
+use basic_lexer::*;
+use basic_lexer::RawToken::*;
+
+fn def() -> LexerDef<'static>
+{
+  let mut def = LexerDef::new();
+  def.add_double("==");
+  def.add_single(';');
+  def.add_single('=');
+  def
+}
+
+fn round_trip(def:&LexerDef, input:&str) -> String
+{
+  let mut output = String::new();
+  for t in LosslessTokenizer::new(def,input) {
+    output.push_str(t.leading);
+    output.push_str(t.text);
+    output.push_str(t.trailing);
+  }
+  output
+}
+
+#[test]
+fn concatenated_text_is_the_input()
+{
+  let def = def();
+  let inputs = [
+    "",
+    "   ",
+    "x",
+    "x = 1;  \n",
+    "// header\n\n\tif (a == 0x1F) { b = \"s\\\"t\"; } /* c */ \r\n  end  ",
+    "/* multi\n line */ y = 'c';\r\n\r\n// last",
+    "a\u{00A0}b\u{3000}c  é  \t\n",
+    "s = \"unclosed\n more",
+  ];
+  for input in inputs.iter() {
+    assert_eq!(round_trip(&def,input), *input);
+  }
+}
+
+#[test]
+fn trivia_is_attached_to_neighbors()
+{
+  let def = def();
+  let tokens:Vec<_> = LosslessTokenizer::new(&def,"// c\nx = 1; // t\n  y\n").collect();
+  assert_eq!(tokens[0].leading, "// c\n");
+  assert_eq!(tokens[0].token, Some(Alphanum("x")));
+  assert_eq!(tokens[0].trailing, " ");
+  assert_eq!(tokens[3].text, ";");
+  assert_eq!(tokens[3].trailing, " // t\n");
+  assert_eq!((tokens[4].leading,tokens[4].text,tokens[4].line,tokens[4].column), ("  ","y",3,3));
+  let last = tokens.last().unwrap();
+  assert_eq!(last.token, None);
+}